@@ -1,15 +1,29 @@
-use crate::crud::executor::{DataMeta, ExecutionResult, execute_query};
-use crate::database::fetch::metadata_to_tree_items;
-use crate::database::pool::DbPool;
+use crate::crud::browse::{TablePage, fetch_page};
+use crate::crud::executor::{DataMeta, ExecutionResult, RowSink};
+use crate::crud::session::{CancelHandle, QuerySession};
+use crate::database::connection::{
+    Connection, default_connection_name, table_path, unique_connection_name,
+};
+use crate::layout::data_table::{BrowsedTable, DynamicData};
 use crate::layout::query_editor::{Mode, Transition};
 use crate::layout::{data_table::DataTable, sidebar::SideBar};
-use crate::state::get_query_stats;
+use crate::style::SPINNER;
+use crate::utils::query_type::{Query, QueryKind};
+use crate::utils::script::{Statement, split_statements, statement_summary};
 use crate::{
     database::{
-        connector::{ConnectionDetails, DatabaseType, get_connection_details},
-        detector::get_installed_databases,
-        fetch::fetch_all_table_metadata,
-        pool::pool,
+        connector::{ConnectionDetails, confirm_new_sqlite_file, get_connection_details},
+        ddl::{object_ddl, table_ddl},
+        detector::{DetectedDatabase, get_installed_databases},
+        fetch::{
+            ByTable, SchemaObject, TableMetadata, TreeFilter, fetch_all_table_metadata,
+            fetch_schema_objects, fetch_search_names, fetch_table_details, fetch_tables_details,
+        },
+        pool::{DbPool, pool},
+        profiles::{
+            ConnectionProfile, load_profiles, prompt_missing_password, prompt_save_profile,
+        },
+        properties::{Properties, object_properties, table_properties},
     },
     layout::query_editor::QueryEditor,
 };
@@ -26,16 +40,18 @@ use ratatui::{
     DefaultTerminal, Frame,
     layout::{Constraint, Direction, Layout},
 };
+use std::collections::HashSet;
 use std::io::Write;
 use std::sync::{
     Arc,
     atomic::{AtomicBool, Ordering},
 };
+use std::time::Instant;
 use std::{io::stdout, time::Duration};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
 use tui_textarea::Input;
-use tui_tree_widget::TreeItem;
 
 #[derive(PartialEq, Debug, Clone)]
 pub enum Focus {
@@ -54,6 +70,109 @@ impl Focus {
     }
 }
 
+/// An entry in the startup picker: a saved profile or a detected engine.
+enum StartupChoice {
+    Profile(ConnectionProfile),
+    Engine(DetectedDatabase),
+}
+
+impl std::fmt::Display for StartupChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StartupChoice::Profile(profile) => write!(f, "⭐ {}", profile),
+            StartupChoice::Engine(detected) => write!(f, "{}", detected),
+        }
+    }
+}
+
+/// Results of background work, delivered to the UI loop.
+enum AppEvent {
+    /// Column and index names of a connection's tables, for the filter.
+    SearchNames {
+        connection: String,
+        result: Result<ByTable<String>>,
+    },
+    /// Details of tables whose sidebar nodes were expanded, in the order of
+    /// their tree paths.
+    TableDetails {
+        paths: Vec<Vec<String>>,
+        result: Result<Vec<TableMetadata>>,
+    },
+    /// A page of a table being browsed, for the page request numbered
+    /// `request`. `table` carries its loaded details.
+    TablePage {
+        request: u64,
+        connection: String,
+        table: Box<TableMetadata>,
+        page: usize,
+        result: Result<TablePage>,
+    },
+    /// Freshly fetched tables and objects of the connection named `connection`.
+    MetadataRefreshed {
+        connection: String,
+        result: Result<(Vec<TableMetadata>, Vec<SchemaObject>)>,
+    },
+    /// Properties of the table or view `name` (`schema.table`).
+    PropertiesLoaded {
+        name: String,
+        result: Result<Properties>,
+    },
+    /// Generated DDL of `name` (`schema.table`), destined for the query editor.
+    DdlGenerated {
+        name: String,
+        result: Result<String>,
+    },
+    /// A batch of rows of result set `result` of the script numbered `script`.
+    ScriptRows {
+        script: u64,
+        result: usize,
+        rows: DynamicData,
+    },
+    /// Result set `result` stopped at the row cap after `fetched` rows.
+    /// `rerun` is its statement, when running that again on the connection
+    /// named `connection` can fetch more.
+    ScriptTruncated {
+        script: u64,
+        result: usize,
+        fetched: usize,
+        connection: String,
+        rerun: Option<String>,
+    },
+    /// The script numbered `script`, run on the connection named
+    /// `connection`, is over.
+    ScriptFinished {
+        script: u64,
+        connection: String,
+        outcome: ScriptOutcome,
+    },
+}
+
+/// A script running in the background.
+struct RunningScript {
+    cancel: CancelHandle,
+}
+
+/// A result cut short at the row cap, whose statement runs again to fetch
+/// the rows after those shown.
+struct MoreRows {
+    connection: String,
+    result: usize,
+    sql: String,
+    fetched: usize,
+}
+
+/// What the statements of a script produced, besides their rows.
+struct ScriptOutcome {
+    /// One message per statement run, plus a note about any not run.
+    messages: Vec<String>,
+    elapsed: Duration,
+    failed: bool,
+    schema_changed: bool,
+}
+
+/// Tables whose details are to be fetched, with their sidebar paths.
+type DetailsQueue = Vec<(Vec<String>, TableMetadata)>;
+
 pub struct App<'a> {
     pub focus: Focus,
     pub query: String,
@@ -61,11 +180,34 @@ pub struct App<'a> {
     pub data_table: DataTable<'a>,
     pub query_editor: QueryEditor,
     pub sidebar: SideBar,
-    pub pool: Option<DbPool>,
+    pub connections: Vec<Connection>,
+    pub active_connection: usize,
+    /// Count SQLite rows exactly when listing tables.
+    pub exact_counts: bool,
+    /// Keep running a script after a statement fails instead of stopping.
+    pub continue_on_error: bool,
+    /// Rows of a result to fetch before stopping, and how many more each
+    /// "fetch more" adds.
+    pub max_rows: usize,
+    open_connection_requested: bool,
+    events_tx: UnboundedSender<AppEvent>,
+    events_rx: UnboundedReceiver<AppEvent>,
+    /// Sidebar paths of tables whose details are being fetched.
+    loading_tables: HashSet<Vec<String>>,
+    running_script: Option<RunningScript>,
+    /// Number of the script run last. Rows of earlier ones are dropped.
+    script_id: u64,
+    /// Result sets the last script has delivered so far.
+    script_results: usize,
+    more_rows: Option<MoreRows>,
+    /// Number of the table page requested last. Pages of earlier requests
+    /// that finish later are dropped.
+    page_request: u64,
 }
 
 impl App<'_> {
     pub fn default() -> Self {
+        let (events_tx, events_rx) = unbounded_channel();
         Self {
             focus: Focus::Sidebar,
             query: String::new(),
@@ -73,69 +215,119 @@ impl App<'_> {
             data_table: DataTable::new(vec![], vec![]),
             query_editor: QueryEditor::new(Mode::Normal),
             sidebar: SideBar::new(vec![], Focus::Sidebar),
-            pool: None,
+            connections: Vec::new(),
+            active_connection: 0,
+            exact_counts: false,
+            continue_on_error: false,
+            max_rows: 10_000,
+            open_connection_requested: false,
+            events_tx,
+            events_rx,
+            loading_tables: HashSet::new(),
+            running_script: None,
+            script_id: 0,
+            script_results: 0,
+            more_rows: None,
+            page_request: 0,
         }
     }
 
-    pub async fn init(&mut self) -> Result<()> {
-        let databases = get_installed_databases()?;
+    pub async fn init(&mut self, details: Option<ConnectionDetails>) -> Result<()> {
+        let connection = match details {
+            Some(details) => {
+                let name = default_connection_name(&details);
+                self.connect(name, details, false).await?
+            }
+            None => match self.choose_connection().await? {
+                Some(connection) => connection,
+                None => {
+                    println!("\n👋 Bye");
+                    return Ok(());
+                }
+            },
+        };
+
+        self.setup_and_run_app(connection).await
+    }
 
-        if databases.is_empty() {
-            println!("❌ No databases detected!");
-            return Ok(());
-        }
+    /// Shows the saved profiles and detected engines, then connects to the pick.
+    /// Returns `None` when the picker is dismissed.
+    async fn choose_connection(&mut self) -> Result<Option<Connection>> {
+        let profiles = load_profiles().unwrap_or_else(|err| {
+            eprintln!("⚠️ Ignoring saved connections: {}", err);
+            Vec::new()
+        });
+        let databases = get_installed_databases().await;
+
+        let choices: Vec<StartupChoice> = profiles
+            .into_iter()
+            .map(StartupChoice::Profile)
+            .chain(databases.into_iter().map(StartupChoice::Engine))
+            .collect();
 
-        let selected = Select::new("🚀 Select a Database", databases.clone())
+        let selected = Select::new("🚀 Select a Database", choices)
             .with_help_message("Use ↑ ↓ arrows, Enter to select")
             .prompt();
 
-        if let Ok(db_name) = selected {
-            if let Some(db_type) = Self::map_db_name_to_type(&db_name) {
-                self.setup_and_run_app(db_type).await?;
-            } else {
-                println!("❌ Unsupported database.");
+        let connection = match selected {
+            Ok(StartupChoice::Profile(mut profile)) => {
+                prompt_missing_password(&mut profile)?;
+                self.connect(profile.name, profile.details, false).await?
             }
-        } else {
-            println!("\n👋 Bye");
-        }
-
-        Ok(())
-    }
+            Ok(StartupChoice::Engine(detected)) => {
+                let details = get_connection_details(&detected)?;
+                let name = default_connection_name(&details);
+                self.connect(name, details, true).await?
+            }
+            Err(_) => return Ok(None),
+        };
 
-    fn map_db_name_to_type(name: &str) -> Option<DatabaseType> {
-        match name.to_lowercase().as_str() {
-            "postgresql" => Some(DatabaseType::PostgreSQL),
-            "mysql" => Some(DatabaseType::MySQL),
-            "sqlite" => Some(DatabaseType::SQLite),
-            _ => None,
-        }
+        Ok(Some(connection))
     }
 
     fn current_query(&self) -> String {
         self.query_editor.textarea.lines().join("\n")
     }
 
-    async fn setup_and_run_app(&mut self, db_type: DatabaseType) -> Result<()> {
-        let details: ConnectionDetails = get_connection_details(db_type)?;
-        let pool = pool(db_type, &details).await?;
+    async fn connect(
+        &mut self,
+        name: String,
+        details: ConnectionDetails,
+        offer_to_save: bool,
+    ) -> Result<Connection> {
+        confirm_new_sqlite_file(&details)?;
+        let pool = pool(details.db_type, &details).await?;
 
-        self.pool = Some(pool.clone());
+        if offer_to_save {
+            prompt_save_profile(&details)?;
+        }
 
         let (spinner_handle, loading) = self.loading().await;
 
-        let metadata = fetch_all_table_metadata(&pool).await?;
+        let (metadata, objects) = tokio::join!(
+            fetch_all_table_metadata(&pool, self.exact_counts),
+            fetch_schema_objects(&pool)
+        );
 
         loading.store(false, Ordering::SeqCst);
         spinner_handle.await.unwrap();
 
-        if metadata.is_empty() {
-            println!("❌ No tables found in the database.");
-            return Ok(());
-        }
+        let name = unique_connection_name(name, &self.connections);
+        Ok(Connection::new(name, details, pool, metadata?, objects?))
+    }
 
-        println!("✅ Found {} tables", metadata.len());
-        let items = metadata_to_tree_items(&metadata);
-        self.setup_ui(items).await?;
+    async fn setup_and_run_app(&mut self, connection: Connection) -> Result<()> {
+        let name = connection.name.clone();
+        let empty = connection.metadata.is_empty() && connection.objects.is_empty();
+        self.add_connection(connection);
+        self.setup_ui().await?;
+        if empty {
+            self.sidebar.state.open(vec![name.clone()]);
+            self.data_table.status_message = Some(format!(
+                "💡 {} is empty. Create tables from the editor, then press r in the sidebar to refresh.",
+                name
+            ));
+        }
 
         stdout().execute(EnableMouseCapture)?;
         let terminal = ratatui::init();
@@ -145,17 +337,65 @@ impl App<'_> {
         Ok(())
     }
 
+    /// Adds a connection to the session and makes it the active one.
+    fn add_connection(&mut self, connection: Connection) {
+        self.connections.push(connection);
+        self.set_active_connection(self.connections.len() - 1);
+    }
+
+    fn set_active_connection(&mut self, index: usize) {
+        if index >= self.connections.len() {
+            return;
+        }
+        self.active_connection = index;
+        self.query_editor.active_connection = Some(self.connections[index].name.clone());
+        self.refresh_sidebar();
+    }
+
+    fn active_connection(&self) -> Option<&Connection> {
+        self.connections.get(self.active_connection)
+    }
+
+    fn refresh_sidebar(&mut self) {
+        let mut filter = TreeFilter::new(&self.sidebar.filter);
+        let items = self
+            .connections
+            .iter()
+            .enumerate()
+            .filter_map(|(i, connection)| {
+                connection.to_tree_item(i == self.active_connection, &mut filter)
+            })
+            .collect();
+        self.sidebar.update_items(items);
+        self.sidebar.update_hits(filter.hits);
+    }
+
+    /// Leaves the TUI to run the connection picker, then adds the result.
+    async fn open_new_connection(&mut self) {
+        match self.choose_connection().await {
+            Ok(Some(connection)) => {
+                let name = connection.name.clone();
+                self.add_connection(connection);
+                self.data_table.status_message = Some(format!("🔌 Connected to {}", name));
+            }
+            Ok(None) => {}
+            Err(err) => {
+                self.data_table.show_messages();
+                self.data_table.status_message = Some(format!("❌ Error: {}", err));
+            }
+        }
+    }
+
     pub async fn loading(&mut self) -> (JoinHandle<()>, Arc<AtomicBool>) {
         let loading = Arc::new(AtomicBool::new(true));
         let spinner_flag = loading.clone();
 
         let spinner_handle = tokio::spawn(async move {
-            let spinner = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
             let mut i = 0;
             let mut stdout = stdout();
 
             while spinner_flag.load(Ordering::SeqCst) {
-                let symbol = spinner[i % spinner.len()];
+                let symbol = SPINNER[i % SPINNER.len()];
                 execute!(
                     stdout,
                     cursor::MoveToColumn(0),
@@ -178,9 +418,9 @@ impl App<'_> {
         (spinner_handle, loading)
     }
 
-    async fn setup_ui(&mut self, sidebar_items: Vec<TreeItem<'static, String>>) -> Result<()> {
+    async fn setup_ui(&mut self) -> Result<()> {
         self.focus = Focus::Sidebar;
-        self.sidebar.update_items(sidebar_items);
+        self.refresh_sidebar();
         self.sidebar.update_focus(Focus::Sidebar);
 
         Ok(())
@@ -190,82 +430,207 @@ impl App<'_> {
         while !self.exit {
             terminal.draw(|f| self.render_ui(f))?;
             let _ = self.handle_events().await;
+            self.handle_app_events();
+
+            if self.open_connection_requested {
+                self.open_connection_requested = false;
+                ratatui::restore();
+                stdout().execute(DisableMouseCapture)?;
+                self.open_new_connection().await;
+                stdout().execute(EnableMouseCapture)?;
+                terminal = ratatui::init();
+            }
         }
         Ok(())
     }
 
     async fn handle_events(&mut self) -> Result<()> {
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key_event) = event::read()? {
-                if key_event.kind == KeyEventKind::Press {
-                    match key_event.code {
-                        KeyCode::Char('q') => {
-                            self.exit = true;
-                        }
-                        KeyCode::Tab => {
-                            self.toggle_focus();
-                        }
-                        KeyCode::F(5) => {
-                            let query = self.current_query();
-                            if !query.is_empty() {
-                                self.query = query.clone();
-
-                                if let Some(pool) = &self.pool {
-                                    match execute_query(pool, &query).await {
-                                        Ok(ExecutionResult::Data(
-                                            data,
-                                            DataMeta { rows: _, message },
-                                        )) => {
-                                            self.data_table = DataTable::new(
-                                                data.headers.clone(),
-                                                data.rows.clone(),
-                                            );
-                                            self.data_table.status_message = Some(message);
-                                            if let Some(stats) = get_query_stats().await {
-                                                self.data_table.elapsed = stats.elapsed
-                                            }
-                                        }
-                                        Ok(ExecutionResult::Affected { rows: _, message }) => {
-                                            self.data_table.status_message = Some(message);
-                                            if let Some(stats) = get_query_stats().await {
-                                                self.data_table.elapsed = stats.elapsed
-                                            }
-                                        }
-                                        Err(err) => {
-                                            self.data_table.tabs.set_index(1);
-                                            self.data_table.status_message =
-                                                Some(format!("❌ Error: {}", err));
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        _ => match self.focus {
-                            Focus::Editor => {
-                                let input = Input::from(key_event);
-                                match self.query_editor.handle_keys(input) {
-                                    Transition::Nop => {}
-                                    Transition::Mode(mode) => self.query_editor.mode = mode,
-                                    Transition::Pending(pending) => {
-                                        self.query_editor.pending = pending
-                                    }
-                                }
-                            }
-                            Focus::Table => self.handle_data_table_keys(key_event.code),
-                            Focus::Sidebar => self.handle_sidebar_keys(key_event.code),
-                        },
+        if event::poll(Duration::from_millis(100))?
+            && let Event::Key(key_event) = event::read()?
+            && key_event.kind == KeyEventKind::Press
+        {
+            match key_event.code {
+                code if self.focus == Focus::Sidebar && self.sidebar.filtering => {
+                    self.handle_sidebar_filter_keys(code);
+                }
+                // Esc still leaves insert and visual mode while a query runs.
+                KeyCode::Esc
+                    if self.running_script.is_some()
+                        && (self.focus != Focus::Editor
+                            || self.query_editor.mode == Mode::Normal) =>
+                {
+                    self.cancel_script();
+                }
+                KeyCode::Char('q') => {
+                    self.exit = true;
+                }
+                KeyCode::Tab => {
+                    self.toggle_focus();
+                }
+                KeyCode::F(2) => {
+                    self.open_connection_requested = true;
+                }
+                KeyCode::F(3) => {
+                    if !self.connections.is_empty() {
+                        let next = (self.active_connection + 1) % self.connections.len();
+                        self.set_active_connection(next);
+                    }
+                }
+                KeyCode::F(5) => self.run_script(self.current_query()),
+                KeyCode::F(6) => {
+                    if let Some(connection) = self.active_connection()
+                        && let Some(sql) = self
+                            .query_editor
+                            .statement_to_run(connection.pool.db_type())
+                    {
+                        self.run_script(sql);
                     }
                 }
+                _ => match self.focus {
+                    Focus::Editor => {
+                        let input = Input::from(key_event);
+                        match self.query_editor.handle_keys(input) {
+                            Transition::Nop => {}
+                            Transition::Mode(mode) => self.query_editor.mode = mode,
+                            Transition::Pending(pending) => self.query_editor.pending = pending,
+                        }
+                    }
+                    Focus::Table => self.handle_data_table_keys(key_event.code),
+                    Focus::Sidebar => self.handle_sidebar_keys(key_event.code),
+                },
             }
         }
         Ok(())
     }
 
+    /// Runs the statements of `script` one by one in the background. Each
+    /// result set gets a tab of its own, filled as rows arrive, and every
+    /// statement's message goes to Messages.
+    fn run_script(&mut self, script: String) {
+        let Some((connection, pool)) = self
+            .active_connection()
+            .map(|c| (c.name.clone(), c.pool.clone()))
+        else {
+            return;
+        };
+        let statements = split_statements(&script, pool.db_type());
+        if statements.is_empty() {
+            return;
+        }
+        if self.running_script.is_some() {
+            self.data_table.status_message =
+                Some("⏳ A query is still running, press Esc to cancel it.".to_string());
+            return;
+        }
+        self.query = script;
+        self.script_results = 0;
+        self.more_rows = None;
+        self.data_table.more_rows = None;
+        self.spawn_script(connection, pool, statements, 0, 0);
+    }
+
+    /// Runs `statements` in the background as the next script. Its first
+    /// result set goes to tab `result`, passing over the first `skip` rows.
+    fn spawn_script(
+        &mut self,
+        connection: String,
+        pool: DbPool,
+        statements: Vec<Statement>,
+        result: usize,
+        skip: usize,
+    ) {
+        let cancel = CancelHandle::default();
+        self.running_script = Some(RunningScript {
+            cancel: cancel.clone(),
+        });
+        self.script_id += 1;
+        // A page still on its way would replace the results of the script.
+        self.page_request += 1;
+        self.data_table.running = Some(Instant::now());
+
+        let script = self.script_id;
+        let continue_on_error = self.continue_on_error;
+        let mut sink = ScriptSink {
+            script,
+            tx: self.events_tx.clone(),
+            connection: connection.clone(),
+            result,
+            streaming: false,
+            max_rows: self.max_rows,
+            skip,
+        };
+        tokio::spawn(async move {
+            let mut outcome =
+                run_statements(&pool, &statements, continue_on_error, &cancel, &mut sink).await;
+            if skip > 0 {
+                outcome.messages.insert(
+                    0,
+                    format!(
+                        "ℹ️ Ran the query again for the rows after the first {skip}. \
+                         Without an ORDER BY, the server may return rows in another order."
+                    ),
+                );
+            }
+            let _ = sink.tx.send(AppEvent::ScriptFinished {
+                script,
+                connection,
+                outcome,
+            });
+        });
+    }
+
+    /// Asks the server to stop the running script.
+    fn cancel_script(&mut self) {
+        let Some(running) = &self.running_script else {
+            return;
+        };
+        let cancel = running.cancel.clone();
+        self.data_table.status_message = Some("⏹️ Cancelling the query...".to_string());
+        tokio::spawn(async move {
+            let _ = cancel.cancel().await;
+        });
+    }
+
+    /// Runs the statement of a result cut short at the row cap again, for
+    /// another `max_rows` rows after those shown.
+    fn fetch_more_rows(&mut self) {
+        if self.running_script.is_some() {
+            return;
+        }
+        let Some(more) = self
+            .more_rows
+            .take_if(|more| self.data_table.more_rows == Some(more.result))
+        else {
+            return;
+        };
+        let Some(pool) = self
+            .connections
+            .iter()
+            .find(|c| c.name == more.connection)
+            .map(|c| c.pool.clone())
+        else {
+            return;
+        };
+        self.data_table.more_rows = None;
+        self.data_table.set_truncated(more.result, false);
+        let statement = Statement {
+            range: 0..more.sql.len(),
+            sql: more.sql,
+        };
+        self.spawn_script(
+            more.connection,
+            pool,
+            vec![statement],
+            more.result,
+            more.fetched,
+        );
+    }
+
     fn handle_data_table_keys(&mut self, key: KeyCode) {
         use KeyCode::*;
         match key {
-            KeyCode::Char('[') => self.data_table.tabs.previous(),
-            KeyCode::Char(']') => self.data_table.tabs.next(),
+            KeyCode::Char('[') => self.data_table.previous_tab(),
+            KeyCode::Char(']') => self.data_table.next_tab(),
 
             Char('j') | Down => self.data_table.next_row(),
             Char('k') | Up => self.data_table.previous_row(),
@@ -273,12 +638,13 @@ impl App<'_> {
             Char('>') => self.data_table.scroll_right(),
             Char('<') => self.data_table.scroll_left(),
 
+            Char('m') => self.fetch_more_rows(),
             Char('n') => self.data_table.next_color(),
             Char('p') => self.data_table.previous_color(),
 
-            PageDown => self.data_table.next_page(),
-            PageUp => self.data_table.previous_page(),
-            Char(' ') => self.data_table.next_page(),
+            PageDown => self.next_page(),
+            PageUp => self.previous_page(),
+            Char(' ') => self.next_page(),
 
             Char('l') | Right => self.data_table.next_column(),
             Char('h') | Left => self.data_table.previous_column(),
@@ -302,10 +668,11 @@ impl App<'_> {
             }
 
             Char(c) if c.is_ascii_digit() => {
-                if let Some(digit) = c.to_digit(10) {
-                    if digit > 0 && (digit as usize) <= self.data_table.tabs.titles.len() {
-                        self.data_table.tabs.set_index(digit as usize - 1);
-                    }
+                if let Some(digit) = c.to_digit(10)
+                    && digit > 0
+                    && (digit as usize) <= self.data_table.tabs.titles.len()
+                {
+                    self.data_table.select_tab(digit as usize - 1);
                 }
             }
 
@@ -314,8 +681,51 @@ impl App<'_> {
     }
     fn handle_sidebar_keys(&mut self, key: KeyCode) {
         use KeyCode::*;
-        match key {
-            Char('\n' | ' ') => self.sidebar.state.toggle_selected(),
+        let changed = match key {
+            Enter if self.selected_table().is_some() => {
+                self.open_selected_table();
+                false
+            }
+            Enter if self.selected_object().is_some() => {
+                self.generate_selected_ddl();
+                false
+            }
+            Enter | Char(' ') => self.sidebar.state.toggle_selected(),
+            Char('d') => {
+                self.generate_selected_ddl();
+                false
+            }
+            Char('/') => {
+                self.sidebar.start_filter();
+                self.refresh_sidebar();
+                self.load_search_names();
+                false
+            }
+            Char('p') => {
+                self.show_selected_properties(false);
+                false
+            }
+            Char('P') => {
+                self.show_selected_properties(true);
+                false
+            }
+            Char('r') => {
+                self.refresh_selected();
+                false
+            }
+            Char('R') => {
+                for index in 0..self.connections.len() {
+                    self.refresh_connection(index);
+                }
+                false
+            }
+            Char('n') => self.sidebar.jump_to_hit(true),
+            Char('N') => self.sidebar.jump_to_hit(false),
+            Esc if !self.sidebar.filter.is_empty() => {
+                self.sidebar.clear_filter();
+                self.refresh_sidebar();
+                true
+            }
             Left => self.sidebar.state.key_left(),
             Right => self.sidebar.state.key_right(),
             Down => self.sidebar.state.key_down(),
@@ -327,6 +737,557 @@ impl App<'_> {
             PageUp => self.sidebar.state.scroll_up(3),
             _ => false,
         };
+
+        if changed {
+            self.follow_sidebar_selection();
+            self.load_expanded_tables();
+        }
+    }
+
+    /// Edits the sidebar filter prompt, selecting the first match as you type.
+    fn handle_sidebar_filter_keys(&mut self, key: KeyCode) {
+        use KeyCode::*;
+        match key {
+            Char(c) => self.sidebar.filter.push(c),
+            Backspace => {
+                self.sidebar.filter.pop();
+            }
+            Enter if !self.sidebar.filter.is_empty() => {
+                self.sidebar.filtering = false;
+                return;
+            }
+            Enter | Esc => self.sidebar.clear_filter(),
+            _ => return,
+        }
+
+        self.refresh_sidebar();
+        if let Some(first) = self.sidebar.hits.first().cloned() {
+            self.sidebar.state.select(first);
+            self.follow_sidebar_selection();
+        }
+    }
+
+    /// Starts fetching the details of every expanded table that has none yet,
+    /// in one batch per connection.
+    fn load_expanded_tables(&mut self) {
+        let mut pending: Vec<(DbPool, DetailsQueue)> = Vec::new();
+        for connection in &self.connections {
+            let tables: DetailsQueue = self
+                .sidebar
+                .state
+                .opened()
+                .iter()
+                .filter(|path| !self.loading_tables.contains(*path))
+                .filter_map(|path| {
+                    let (name, schema, table) = table_path(path)?;
+                    if name != connection.name {
+                        return None;
+                    }
+                    let table = connection
+                        .metadata
+                        .iter()
+                        .find(|t| t.schema == schema && t.name == table && !t.details_loaded)?;
+                    Some((path.clone(), table.clone()))
+                })
+                .collect();
+            if !tables.is_empty() {
+                pending.push((connection.pool.clone(), tables));
+            }
+        }
+
+        for (pool, tables) in pending {
+            self.load_table_details(pool, tables);
+        }
+    }
+
+    /// Fetches the column and index names of connections with unloaded
+    /// tables, one query each, so the sidebar filter can match those too.
+    fn load_search_names(&mut self) {
+        for connection in &self.connections {
+            if !connection
+                .metadata
+                .iter()
+                .any(|table| !table.details_loaded && table.search_names.is_none())
+            {
+                continue;
+            }
+            let name = connection.name.clone();
+            let pool = connection.pool.clone();
+            let tx = self.events_tx.clone();
+            tokio::spawn(async move {
+                let result = fetch_search_names(&pool).await;
+                let _ = tx.send(AppEvent::SearchNames {
+                    connection: name,
+                    result,
+                });
+            });
+        }
+    }
+
+    /// Fetches the details of `tables` together in the background.
+    fn load_table_details(&mut self, pool: DbPool, tables: DetailsQueue) {
+        let (paths, tables): (Vec<_>, Vec<_>) = tables.into_iter().unzip();
+        self.loading_tables.extend(paths.iter().cloned());
+        let tx = self.events_tx.clone();
+        tokio::spawn(async move {
+            let result = fetch_tables_details(&pool, &tables).await;
+            let _ = tx.send(AppEvent::TableDetails { paths, result });
+        });
+    }
+
+    /// Reloads the details of the table the selection is in, or everything
+    /// in the selected connection when the selection is outside a table.
+    fn refresh_selected(&mut self) {
+        let selected = self.sidebar.state.selected();
+        let table =
+            selected
+                .get(..4)
+                .and_then(table_path)
+                .and_then(|(connection, schema, name)| {
+                    let connection = self.connections.iter().find(|c| c.name == connection)?;
+                    let table = connection
+                        .metadata
+                        .iter()
+                        .find(|t| t.schema == schema && t.name == name)?;
+                    Some((
+                        connection.pool.clone(),
+                        selected[..4].to_vec(),
+                        table.clone(),
+                    ))
+                });
+
+        if let Some((pool, path, table)) = table {
+            if self.loading_tables.contains(&path) {
+                return;
+            }
+            self.load_table_details(pool, vec![(path, table)]);
+        } else if let Some(index) = selected
+            .first()
+            .and_then(|root| self.connections.iter().position(|c| &c.name == root))
+        {
+            self.refresh_connection(index);
+        }
+    }
+
+    /// Fetches the tables and objects of a connection again in the background.
+    fn refresh_connection(&mut self, index: usize) {
+        let Some(connection) = self.connections.get(index) else {
+            return;
+        };
+        let name = connection.name.clone();
+        let pool = connection.pool.clone();
+        let exact_counts = self.exact_counts;
+        let tx = self.events_tx.clone();
+
+        self.data_table.status_message = Some(format!("🔄 Refreshing {}…", name));
+        tokio::spawn(async move {
+            let (metadata, objects) = tokio::join!(
+                fetch_all_table_metadata(&pool, exact_counts),
+                fetch_schema_objects(&pool)
+            );
+            let result = metadata.and_then(|metadata| Ok((metadata, objects?)));
+            let _ = tx.send(AppEvent::MetadataRefreshed {
+                connection: name,
+                result,
+            });
+        });
+    }
+
+    /// Returns the connection and metadata of the selected table or view node.
+    fn selected_table(&self) -> Option<(&Connection, &TableMetadata)> {
+        let (connection, schema, table) = table_path(self.sidebar.state.selected())?;
+        let connection = self.connections.iter().find(|c| c.name == connection)?;
+        let table = connection
+            .metadata
+            .iter()
+            .find(|t| t.schema == schema && t.name == table)?;
+        Some((connection, table))
+    }
+
+    /// Returns the connection and the function, sequence, type or extension
+    /// of the selected node.
+    fn selected_object(&self) -> Option<(&Connection, &SchemaObject)> {
+        let selected = self.sidebar.state.selected();
+        let connection = self
+            .connections
+            .iter()
+            .find(|c| Some(&c.name) == selected.first())?;
+        Some((connection, connection.object_at(selected)?))
+    }
+
+    /// Opens the selected table in the data table, starting at its first page.
+    fn open_selected_table(&mut self) {
+        let Some((connection, table)) = self.selected_table() else {
+            return;
+        };
+        let (connection, table) = (connection.name.clone(), table.clone());
+
+        self.data_table.status_message =
+            Some(format!("⏳ Opening {}.{}…", table.schema, table.name));
+        self.load_table_page(connection, table, 0);
+    }
+
+    /// Puts the `CREATE` statements of the selected table, or the source of
+    /// the selected object, into the query editor.
+    fn generate_selected_ddl(&mut self) {
+        let tx = self.events_tx.clone();
+
+        if let Some((connection, table)) = self.selected_table() {
+            let pool = connection.pool.clone();
+            let table = table.clone();
+            tokio::spawn(async move {
+                let result = table_ddl(&pool, &table).await;
+                let name = format!("{}.{}", table.schema, table.name);
+                let _ = tx.send(AppEvent::DdlGenerated { name, result });
+            });
+        } else if let Some((connection, object)) = self.selected_object() {
+            let pool = connection.pool.clone();
+            let object = object.clone();
+            tokio::spawn(async move {
+                let result = object_ddl(&pool, &object).await;
+                let _ = tx.send(AppEvent::DdlGenerated {
+                    name: object.name,
+                    result,
+                });
+            });
+        }
+    }
+
+    /// Shows the properties of the selected table, view or object in the
+    /// Properties tab. Tables are inspected in the background, and their rows
+    /// counted only with `count_exactly`.
+    fn show_selected_properties(&mut self, count_exactly: bool) {
+        if let Some((connection, table)) = self.selected_table() {
+            let pool = connection.pool.clone();
+            let table = table.clone();
+            let tx = self.events_tx.clone();
+            self.data_table.status_message = Some(match count_exactly {
+                true => format!("⏳ Counting the rows of {}.{}…", table.schema, table.name),
+                false => format!("⏳ Inspecting {}.{}…", table.schema, table.name),
+            });
+            tokio::spawn(async move {
+                let result = table_properties(&pool, &table, count_exactly).await;
+                let name = format!("{}.{}", table.schema, table.name);
+                let _ = tx.send(AppEvent::PropertiesLoaded { name, result });
+            });
+        } else if let Some((_, object)) = self.selected_object() {
+            self.data_table.properties = Some(object_properties(object));
+            self.data_table.show_properties();
+        }
+    }
+
+    /// Fetches one page of a table in the background. The first page also
+    /// counts the rows, and loads the table's columns if the sidebar has not.
+    /// A running script keeps the data table until it is over.
+    fn load_table_page(&mut self, connection: String, table: TableMetadata, page: usize) {
+        if self.running_script.is_some() {
+            self.data_table.status_message =
+                Some("⏳ A query is still running, press Esc to cancel it.".to_string());
+            return;
+        }
+        let Some(pool) = self
+            .connections
+            .iter()
+            .find(|c| c.name == connection)
+            .map(|c| c.pool.clone())
+        else {
+            return;
+        };
+        let page_size = self.data_table.page_size();
+        let tx = self.events_tx.clone();
+        self.page_request += 1;
+        let request = self.page_request;
+
+        tokio::spawn(async move {
+            let mut table = table;
+            let result = async {
+                if !table.details_loaded {
+                    table = fetch_table_details(&pool, &table).await?;
+                }
+                fetch_page(&pool, &table, page, page_size, page == 0).await
+            }
+            .await;
+            let _ = tx.send(AppEvent::TablePage {
+                request,
+                connection,
+                table: Box::new(table),
+                page,
+                result,
+            });
+        });
+    }
+
+    fn next_page(&mut self) {
+        match &self.data_table.browsing {
+            Some(browsing) => {
+                let page = self.data_table.current_page + 1;
+                if page < self.data_table.total_pages() {
+                    self.load_table_page(browsing.connection.clone(), browsing.table.clone(), page);
+                }
+            }
+            None => self.data_table.next_page(),
+        }
+    }
+
+    fn previous_page(&mut self) {
+        match &self.data_table.browsing {
+            Some(browsing) => {
+                if let Some(page) = self.data_table.current_page.checked_sub(1) {
+                    self.load_table_page(browsing.connection.clone(), browsing.table.clone(), page);
+                }
+            }
+            None => self.data_table.previous_page(),
+        }
+    }
+
+    /// Applies the results of finished background work.
+    fn handle_app_events(&mut self) {
+        while let Ok(event) = self.events_rx.try_recv() {
+            match event {
+                AppEvent::SearchNames { connection, result } => {
+                    let Some(connection) =
+                        self.connections.iter_mut().find(|c| c.name == connection)
+                    else {
+                        continue;
+                    };
+                    match result {
+                        Ok(mut names) => {
+                            for table in &mut connection.metadata {
+                                let key = (table.schema.clone(), table.name.clone());
+                                table.search_names = Some(names.remove(&key).unwrap_or_default());
+                            }
+                            self.refresh_sidebar();
+                        }
+                        Err(err) => {
+                            self.data_table.status_message = Some(format!(
+                                "⚠️ Filtering {} by table names only: {}",
+                                connection.name, err
+                            ));
+                        }
+                    }
+                }
+                AppEvent::TableDetails { paths, result } => {
+                    for path in &paths {
+                        self.loading_tables.remove(path);
+                    }
+                    match result {
+                        Ok(loaded) => {
+                            for (path, details) in paths.iter().zip(loaded) {
+                                let Some((connection, schema, table)) = table_path(path) else {
+                                    continue;
+                                };
+                                if let Some(target) = self
+                                    .connections
+                                    .iter_mut()
+                                    .find(|c| c.name == connection)
+                                    .and_then(|c| c.table_mut(schema, table))
+                                {
+                                    *target = details;
+                                }
+                            }
+                        }
+                        Err(err) => {
+                            for path in &paths {
+                                self.sidebar.state.close(path);
+                            }
+                            let names: Vec<String> = paths
+                                .iter()
+                                .filter_map(|path| table_path(path))
+                                .map(|(_, schema, table)| format!("{}.{}", schema, table))
+                                .collect();
+                            self.data_table.show_messages();
+                            self.data_table.status_message =
+                                Some(format!("❌ Could not load {}: {}", names.join(", "), err));
+                        }
+                    }
+                    self.refresh_sidebar();
+                }
+                AppEvent::TablePage { request, .. } if request != self.page_request => {}
+                AppEvent::TablePage {
+                    connection,
+                    table,
+                    page,
+                    result,
+                    ..
+                } => match result {
+                    Ok(TablePage {
+                        data,
+                        total_rows,
+                        elapsed,
+                    }) => {
+                        let total_rows = total_rows
+                            .or_else(|| self.data_table.browsing.as_ref().map(|b| b.total_rows))
+                            .unwrap_or_default();
+                        let name = format!("{}.{}", table.schema, table.name);
+                        if let Some(target) = self
+                            .connections
+                            .iter_mut()
+                            .find(|c| c.name == connection)
+                            .and_then(|c| c.table_mut(&table.schema, &table.name))
+                            && !target.details_loaded
+                        {
+                            *target = (*table).clone();
+                            self.refresh_sidebar();
+                        }
+
+                        let browsing = BrowsedTable {
+                            connection,
+                            table: *table,
+                            total_rows,
+                        };
+                        self.data_table = DataTable::browse(browsing, page, data);
+                        self.data_table.elapsed = elapsed;
+                        self.data_table.status_message = Some(format!(
+                            "📋 {}: page {} of {}, {} rows in total.",
+                            name,
+                            page + 1,
+                            self.data_table.total_pages(),
+                            total_rows
+                        ));
+                        self.focus = Focus::Table;
+                        self.sidebar.update_focus(Focus::Table);
+                    }
+                    Err(err) => {
+                        self.data_table.show_messages();
+                        self.data_table.status_message = Some(format!(
+                            "❌ Could not open {}.{}: {}",
+                            table.schema, table.name, err
+                        ));
+                    }
+                },
+                AppEvent::MetadataRefreshed { connection, result } => match result {
+                    Ok((metadata, objects)) => {
+                        let Some(target) =
+                            self.connections.iter_mut().find(|c| c.name == connection)
+                        else {
+                            continue;
+                        };
+                        target.metadata = metadata;
+                        target.objects = objects;
+
+                        // Tree state is keyed by path, so expanded and selected
+                        // nodes survive; expanded tables fetch their details again.
+                        self.refresh_sidebar();
+                        self.load_expanded_tables();
+                        self.data_table.status_message =
+                            Some(format!("🔄 Refreshed {}", connection));
+                    }
+                    Err(err) => {
+                        self.data_table.show_messages();
+                        self.data_table.status_message =
+                            Some(format!("❌ Could not refresh {}: {}", connection, err));
+                    }
+                },
+                AppEvent::PropertiesLoaded { name, result } => match result {
+                    Ok(properties) => {
+                        self.data_table.properties = Some(properties);
+                        self.data_table.show_properties();
+                        self.data_table.status_message =
+                            Some(format!("ℹ️ Properties of {} loaded", name));
+                    }
+                    Err(err) => {
+                        self.data_table.show_messages();
+                        self.data_table.status_message =
+                            Some(format!("❌ Could not inspect {}: {}", name, err));
+                    }
+                },
+                AppEvent::ScriptRows {
+                    script,
+                    result,
+                    rows,
+                } => {
+                    if script != self.script_id {
+                        continue;
+                    }
+                    if result < self.script_results {
+                        self.data_table.extend_result(result, rows);
+                    } else if result == 0 {
+                        // The first rows replace whatever was shown before.
+                        let running = self.data_table.running;
+                        self.data_table = DataTable::from_results(vec![rows]);
+                        self.data_table.running = running;
+                        self.script_results = 1;
+                    } else {
+                        self.data_table.add_result(rows);
+                        self.script_results += 1;
+                    }
+                }
+                AppEvent::ScriptTruncated {
+                    script,
+                    result,
+                    fetched,
+                    connection,
+                    rerun,
+                } => {
+                    if script != self.script_id {
+                        continue;
+                    }
+                    self.data_table.set_truncated(result, true);
+                    if let Some(sql) = rerun {
+                        self.more_rows = Some(MoreRows {
+                            connection,
+                            result,
+                            sql,
+                            fetched,
+                        });
+                        self.data_table.more_rows = Some(result);
+                    }
+                }
+                AppEvent::ScriptFinished {
+                    script,
+                    connection,
+                    outcome,
+                } => {
+                    if script != self.script_id {
+                        continue;
+                    }
+                    self.running_script = None;
+                    if self.script_results == 0 {
+                        // Rows of an earlier run would pass for this one's.
+                        self.data_table = DataTable::new(vec![], vec![]);
+                    }
+                    if outcome.failed || self.data_table.is_empty() {
+                        self.data_table.show_messages();
+                    }
+                    self.data_table.running = None;
+                    self.data_table.elapsed = outcome.elapsed;
+                    self.data_table.status_message = Some(outcome.messages.join("\n\n"));
+
+                    if outcome.schema_changed
+                        && let Some(index) =
+                            self.connections.iter().position(|c| c.name == connection)
+                    {
+                        self.refresh_connection(index);
+                    }
+                }
+                AppEvent::DdlGenerated { name, result } => match result {
+                    Ok(ddl) => {
+                        self.query_editor.insert_paragraph(&ddl);
+                        self.data_table.status_message =
+                            Some(format!("📝 DDL of {} added to the editor", name));
+                        self.focus = Focus::Editor;
+                        self.sidebar.update_focus(Focus::Editor);
+                    }
+                    Err(err) => {
+                        self.data_table.show_messages();
+                        self.data_table.status_message =
+                            Some(format!("❌ Could not generate DDL of {}: {}", name, err));
+                    }
+                },
+            }
+        }
+    }
+
+    /// Makes the connection that owns the selected sidebar node the active one.
+    fn follow_sidebar_selection(&mut self) {
+        let Some(root) = self.sidebar.state.selected().first() else {
+            return;
+        };
+        if let Some(index) = self.connections.iter().position(|c| &c.name == root)
+            && index != self.active_connection
+        {
+            self.set_active_connection(index);
+        }
     }
 
     fn render_ui(&mut self, f: &mut Frame) {
@@ -350,3 +1311,149 @@ impl App<'_> {
         self.sidebar.update_focus(self.focus.clone());
     }
 }
+
+/// Sends the rows of a script's result sets to the UI. Only the last
+/// statement waits at the row cap for more to be asked for; earlier ones
+/// are truncated so the script can go on.
+struct ScriptSink {
+    script: u64,
+    tx: UnboundedSender<AppEvent>,
+    connection: String,
+    /// Index of the result set being streamed.
+    result: usize,
+    /// Whether that result set has sent rows yet.
+    streaming: bool,
+    max_rows: usize,
+    /// Rows of the first result set already shown.
+    skip: usize,
+}
+
+impl ScriptSink {
+    /// Moves on to a new result set once the current one sent rows, even
+    /// when its statement failed halfway.
+    fn next_statement(&mut self) {
+        if self.streaming {
+            self.result += 1;
+            self.streaming = false;
+            self.skip = 0;
+        }
+    }
+
+    /// Reports that the result set of `rerun`, if it can run again, was
+    /// cut short after `rows` rows.
+    fn truncated(&self, rows: usize, rerun: Option<&str>) {
+        let _ = self.tx.send(AppEvent::ScriptTruncated {
+            script: self.script,
+            result: self.result,
+            fetched: self.skip + rows,
+            connection: self.connection.clone(),
+            rerun: rerun.map(str::to_string),
+        });
+    }
+}
+
+impl RowSink for ScriptSink {
+    fn limit(&self) -> usize {
+        self.max_rows
+    }
+
+    fn skip(&self) -> usize {
+        self.skip
+    }
+
+    fn rows(&mut self, batch: DynamicData) {
+        self.streaming = true;
+        let _ = self.tx.send(AppEvent::ScriptRows {
+            script: self.script,
+            result: self.result,
+            rows: batch,
+        });
+    }
+}
+
+/// Runs `statements` in order on one connection of `pool`. A failure stops
+/// the rest unless `continue_on_error` is set; a cancel always does.
+async fn run_statements(
+    pool: &DbPool,
+    statements: &[Statement],
+    continue_on_error: bool,
+    cancel: &CancelHandle,
+    sink: &mut ScriptSink,
+) -> ScriptOutcome {
+    let mut outcome = ScriptOutcome {
+        messages: Vec::new(),
+        elapsed: Duration::ZERO,
+        failed: false,
+        schema_changed: false,
+    };
+    let mut session = match QuerySession::open(pool, Some(cancel.clone())).await {
+        Ok(session) => session,
+        Err(err) => {
+            outcome.failed = true;
+            outcome.messages.push(format!("❌ Error: {}", err));
+            return outcome;
+        }
+    };
+
+    let numbered = statements.len() > 1;
+    for (i, statement) in statements.iter().enumerate() {
+        if cancel.is_cancelled() {
+            outcome.messages.push(format!(
+                "⏹️ Cancelled: {} statement(s) not run.",
+                statements.len() - i
+            ));
+            break;
+        }
+
+        sink.next_statement();
+        let query = Query::from_sql(&statement.sql, pool.db_type());
+        let message = match session.execute(&statement.sql, sink).await {
+            Ok(ExecutionResult::Data(DataMeta {
+                rows,
+                message,
+                elapsed,
+                truncated,
+            })) => {
+                if truncated {
+                    // Only read-only queries are safe to run again for more rows.
+                    let rerun = query.read_only.then_some(statement.sql.as_str());
+                    sink.truncated(rows, rerun);
+                }
+                outcome.elapsed += elapsed;
+                message
+            }
+            Ok(ExecutionResult::Affected {
+                message, elapsed, ..
+            }) => {
+                outcome.elapsed += elapsed;
+                outcome.schema_changed |= query.kind == QueryKind::Ddl;
+                message
+            }
+            Err(err) => {
+                outcome.failed = true;
+                format!("❌ Error: {}", err)
+            }
+        };
+
+        outcome.messages.push(match numbered {
+            true => format!(
+                "[{}] {}\n{}",
+                i + 1,
+                statement_summary(&statement.sql),
+                message
+            ),
+            false => message,
+        });
+        if outcome.failed && !continue_on_error && !cancel.is_cancelled() {
+            let skipped = statements.len() - i - 1;
+            if skipped > 0 {
+                outcome
+                    .messages
+                    .push(format!("⏭️ Stopped: {} statement(s) not run.", skipped));
+            }
+            break;
+        }
+    }
+    session.close().await;
+    outcome
+}
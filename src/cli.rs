@@ -0,0 +1,191 @@
+use crate::database::connector::{ConnectionDetails, DatabaseType};
+use clap::{Parser, ValueEnum};
+use color_eyre::eyre::Result;
+use std::num::NonZeroUsize;
+
+/// Environment variable consulted when no connection target is passed.
+const DATABASE_URL_ENV: &str = "DATABASE_URL";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Engine {
+    Postgres,
+    Mysql,
+    Sqlite,
+}
+
+impl From<Engine> for DatabaseType {
+    fn from(engine: Engine) -> Self {
+        match engine {
+            Engine::Postgres => DatabaseType::PostgreSQL,
+            Engine::Mysql => DatabaseType::MySQL,
+            Engine::Sqlite => DatabaseType::SQLite,
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Connection URL (postgres://, mysql://, sqlite://) or path to a SQLite file.
+    /// Falls back to $DATABASE_URL when omitted.
+    pub target: Option<String>,
+
+    /// Database engine to use with --host/--port/--user/--db
+    #[arg(short = 't', long = "type", value_enum)]
+    pub engine: Option<Engine>,
+
+    /// Database server host
+    #[arg(short = 'H', long)]
+    pub host: Option<String>,
+
+    /// Database server port
+    #[arg(short = 'P', long)]
+    pub port: Option<u16>,
+
+    /// User name to connect as
+    #[arg(short = 'u', long)]
+    pub user: Option<String>,
+
+    /// Password to connect with
+    #[arg(short = 'p', long)]
+    pub password: Option<String>,
+
+    /// Database name to connect to
+    #[arg(short = 'd', long)]
+    pub db: Option<String>,
+
+    /// Count SQLite rows with COUNT(*) instead of ANALYZE statistics
+    #[arg(long)]
+    pub exact_counts: bool,
+
+    /// Keep running a script after one of its statements fails
+    #[arg(long)]
+    pub continue_on_error: bool,
+
+    /// Rows of a query result to fetch before stopping; m in the results fetches as many more
+    #[arg(long, default_value = "10000")]
+    pub max_rows: NonZeroUsize,
+}
+
+impl Cli {
+    /// Resolves the arguments into connection details.
+    ///
+    /// Returns `None` when nothing was given, so the caller can fall back to
+    /// the interactive prompts.
+    pub fn connection_details(&self) -> Result<Option<ConnectionDetails>> {
+        let target = self
+            .target
+            .clone()
+            .or_else(|| std::env::var(DATABASE_URL_ENV).ok())
+            .filter(|t| !t.trim().is_empty());
+
+        let base = match target {
+            Some(target) if target.contains("://") || target.starts_with("sqlite:") => {
+                Some(ConnectionDetails::from_url(&target)?)
+            }
+            Some(path) => Some(ConnectionDetails::sqlite(&path)),
+            None => None,
+        };
+
+        if base.is_none() && !self.has_connection_flags() {
+            return Ok(None);
+        }
+
+        let mut details = base.unwrap_or_else(|| {
+            let db_type = self.engine.unwrap_or(Engine::Postgres).into();
+            ConnectionDetails {
+                db_type,
+                host: None,
+                port: None,
+                username: None,
+                password: None,
+                database: None,
+                file_path: None,
+                params: Vec::new(),
+            }
+        });
+
+        if let Some(engine) = self.engine {
+            details.db_type = engine.into();
+        }
+        if self.host.is_some() {
+            details.host = self.host.clone();
+        }
+        if self.port.is_some() {
+            details.port = self.port;
+        }
+        if self.user.is_some() {
+            details.username = self.user.clone();
+        }
+        if self.password.is_some() {
+            details.password = self.password.clone();
+        }
+        if self.db.is_some() {
+            match details.db_type {
+                DatabaseType::SQLite => details.file_path = self.db.clone(),
+                _ => details.database = self.db.clone(),
+            }
+        }
+
+        Ok(Some(details))
+    }
+
+    fn has_connection_flags(&self) -> bool {
+        self.engine.is_some()
+            || self.host.is_some()
+            || self.port.is_some()
+            || self.user.is_some()
+            || self.password.is_some()
+            || self.db.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details(args: &[&str]) -> ConnectionDetails {
+        let cli =
+            Cli::try_parse_from(std::iter::once("lazydata").chain(args.iter().copied())).unwrap();
+        cli.connection_details().unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_postgres_url_target() {
+        let details = details(&["postgres://user@db.internal:6432/app"]);
+        assert_eq!(details.db_type, DatabaseType::PostgreSQL);
+        assert_eq!(details.host.as_deref(), Some("db.internal"));
+        assert_eq!(details.port, Some(6432));
+        assert_eq!(details.username.as_deref(), Some("user"));
+        assert_eq!(details.database.as_deref(), Some("app"));
+    }
+
+    #[test]
+    fn test_sqlite_path_target() {
+        let details = details(&["./app.db"]);
+        assert_eq!(details.db_type, DatabaseType::SQLite);
+        assert_eq!(details.file_path.as_deref(), Some("./app.db"));
+    }
+
+    #[test]
+    fn test_flags_override_url() {
+        let details = details(&[
+            "mysql://root@localhost/shop",
+            "--port",
+            "3307",
+            "--db",
+            "dev",
+        ]);
+        assert_eq!(details.db_type, DatabaseType::MySQL);
+        assert_eq!(details.port, Some(3307));
+        assert_eq!(details.database.as_deref(), Some("dev"));
+    }
+
+    #[test]
+    fn test_flags_without_target() {
+        let details = details(&["--type", "mysql", "--host", "10.0.0.5", "--user", "app"]);
+        assert_eq!(details.db_type, DatabaseType::MySQL);
+        assert_eq!(details.host.as_deref(), Some("10.0.0.5"));
+        assert_eq!(details.username.as_deref(), Some("app"));
+    }
+}
@@ -6,12 +6,13 @@ use ratatui::{
 };
 
 use crate::style::theme::{COLOR_BLACK, COLOR_FOCUS};
+use std::borrow::Cow;
 
 // --- Reusable StatefulTabs Component ---
 /// A component to manage and render tabs.
 pub struct StatefulTabs<'a> {
     /// Titles of the tabs.
-    pub titles: Vec<&'a str>,
+    pub titles: Vec<Cow<'a, str>>,
     /// The index of the currently selected tab.
     pub index: usize,
 }
@@ -19,13 +20,15 @@ pub struct StatefulTabs<'a> {
 impl<'a> StatefulTabs<'a> {
     /// Creates a new `StatefulTabs` component with the given titles.
     /// The first tab is selected by default.
-    pub fn new(titles: Vec<&'a str>) -> Self {
+    pub fn new<T: Into<Cow<'a, str>>>(titles: Vec<T>) -> Self {
+        let titles = titles.into_iter().map(Into::into).collect();
         StatefulTabs { titles, index: 0 }
     }
 
     /// Creates a new `StatefulTabs` component with an initial selected index.
     #[allow(dead_code)] // Example: could be used if needed
-    pub fn with_initial_index(titles: Vec<&'a str>, initial_index: usize) -> Self {
+    pub fn with_initial_index<T: Into<Cow<'a, str>>>(titles: Vec<T>, initial_index: usize) -> Self {
+        let titles: Vec<Cow<'a, str>> = titles.into_iter().map(Into::into).collect();
         let max_index = titles.len().saturating_sub(1);
         let index = initial_index.min(max_index);
         StatefulTabs { titles, index }
@@ -62,7 +65,7 @@ impl<'a> StatefulTabs<'a> {
         let titles_as_lines: Vec<Line> = self
             .titles
             .iter()
-            .map(|t| Line::from(Span::styled(*t, Style::default())))
+            .map(|t| Line::from(Span::styled(t.clone(), Style::default())))
             .collect();
 
         Tabs::new(titles_as_lines)
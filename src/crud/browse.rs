@@ -0,0 +1,195 @@
+use super::executor::execute_query;
+use crate::database::connector::DatabaseType;
+use crate::database::fetch::TableMetadata;
+use crate::database::pool::DbPool;
+use crate::layout::data_table::DynamicData;
+use crate::utils::query_timer::query_timer;
+use color_eyre::eyre::Result;
+use std::time::Duration;
+
+/// One page of rows of a table opened from the sidebar.
+pub struct TablePage {
+    pub data: DynamicData,
+    /// Exact row count, only queried when the table is first opened.
+    pub total_rows: Option<usize>,
+    pub elapsed: Duration,
+}
+
+/// Quotes an identifier the way the engine expects it.
+pub fn quote_identifier(db_type: DatabaseType, name: &str) -> String {
+    match db_type {
+        DatabaseType::MySQL => format!("`{}`", name.replace('`', "``")),
+        DatabaseType::PostgreSQL | DatabaseType::SQLite => {
+            format!("\"{}\"", name.replace('"', "\"\""))
+        }
+    }
+}
+
+/// Returns `schema.table`, quoted for the engine.
+pub fn qualified_name(db_type: DatabaseType, table: &TableMetadata) -> String {
+    format!(
+        "{}.{}",
+        quote_identifier(db_type, &table.schema),
+        quote_identifier(db_type, &table.name)
+    )
+}
+
+/// Builds the query for one page of a table, in an order that keeps pages
+/// stable while paging back and forth: the primary key, else the physical
+/// row id, else every column.
+pub fn page_sql(
+    db_type: DatabaseType,
+    table: &TableMetadata,
+    page: usize,
+    page_size: usize,
+) -> String {
+    let mut order_by: Vec<String> = table
+        .columns
+        .iter()
+        .filter(|column| column.primary_key)
+        .map(|column| quote_identifier(db_type, &column.name))
+        .collect();
+    if order_by.is_empty() {
+        order_by = match (db_type, table.table_type.as_str()) {
+            (DatabaseType::PostgreSQL, "table" | "materialized view") => vec!["ctid".to_string()],
+            // Row ids repeat across partitions.
+            (DatabaseType::PostgreSQL, "partitioned table") => {
+                vec!["tableoid".to_string(), "ctid".to_string()]
+            }
+            // Whole rows as text, since columns such as json cannot be sorted.
+            (DatabaseType::PostgreSQL, _) => vec!["ROW(page.*)::TEXT".to_string()],
+            // Tables without a rowid must have a primary key.
+            (DatabaseType::SQLite, "table") => vec!["rowid".to_string()],
+            _ => table
+                .columns
+                .iter()
+                .map(|column| quote_identifier(db_type, &column.name))
+                .collect(),
+        };
+    }
+
+    let mut sql = format!("SELECT * FROM {} AS page", qualified_name(db_type, table));
+    if !order_by.is_empty() {
+        sql.push_str(&format!(" ORDER BY {}", order_by.join(", ")));
+    }
+    sql.push_str(&format!(" LIMIT {} OFFSET {}", page_size, page * page_size));
+    sql
+}
+
+pub async fn count_rows(pool: &DbPool, table: &TableMetadata) -> Result<usize> {
+    let sql = format!(
+        "SELECT COUNT(*) FROM {}",
+        qualified_name(pool.db_type(), table)
+    );
+    let count: i64 = match pool {
+        DbPool::Postgres(pg) => sqlx::query_scalar(&sql).fetch_one(pg).await?,
+        DbPool::MySQL(mysql) => sqlx::query_scalar(&sql).fetch_one(mysql).await?,
+        DbPool::SQLite(sqlite) => sqlx::query_scalar(&sql).fetch_one(sqlite).await?,
+    };
+    Ok(count.max(0) as usize)
+}
+
+/// Fetches one page of `table`, counting its rows too when `count` is set.
+///
+/// The table's columns must be loaded: they give the page order and the
+/// headers of an empty page.
+pub async fn fetch_page(
+    pool: &DbPool,
+    table: &TableMetadata,
+    page: usize,
+    page_size: usize,
+    count: bool,
+) -> Result<TablePage> {
+    let sql = page_sql(pool.db_type(), table, page, page_size);
+    let (result, elapsed) = query_timer(execute_query(pool, &sql)).await;
+
+    let mut data = result?;
+    if data.headers.is_empty() {
+        let headers = table.columns.iter().map(|c| c.name.clone()).collect();
+        data = DynamicData::new(headers, vec![]);
+    }
+
+    let total_rows = if count {
+        Some(count_rows(pool, table).await?)
+    } else {
+        None
+    };
+
+    Ok(TablePage {
+        data,
+        total_rows,
+        elapsed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::fetch::ColumnMetadata;
+
+    fn invoices() -> TableMetadata {
+        TableMetadata {
+            schema: "billing".to_string(),
+            name: "invoices".to_string(),
+            columns: vec![
+                ColumnMetadata {
+                    name: "id".to_string(),
+                    primary_key: true,
+                    ..ColumnMetadata::default()
+                },
+                ColumnMetadata {
+                    name: "amount".to_string(),
+                    ..ColumnMetadata::default()
+                },
+            ],
+            ..TableMetadata::default()
+        }
+    }
+
+    #[test]
+    fn test_page_sql_orders_by_primary_key() {
+        assert_eq!(
+            page_sql(DatabaseType::PostgreSQL, &invoices(), 2, 100),
+            r#"SELECT * FROM "billing"."invoices" AS page ORDER BY "id" LIMIT 100 OFFSET 200"#
+        );
+        assert_eq!(
+            page_sql(DatabaseType::MySQL, &invoices(), 0, 50),
+            "SELECT * FROM `billing`.`invoices` AS page ORDER BY `id` LIMIT 50 OFFSET 0"
+        );
+    }
+
+    #[test]
+    fn test_page_sql_without_primary_key_is_still_ordered() {
+        let mut table = invoices();
+        table.columns[0].primary_key = false;
+        table.table_type = "table".to_string();
+        let order = |db_type, table_type: &str| {
+            let table = TableMetadata {
+                table_type: table_type.to_string(),
+                ..table.clone()
+            };
+            let sql = page_sql(db_type, &table, 0, 10);
+            let start = sql.find(" ORDER BY ").expect("no ORDER BY") + 10;
+            sql[start..sql.find(" LIMIT").unwrap()].to_string()
+        };
+
+        assert_eq!(order(DatabaseType::PostgreSQL, "table"), "ctid");
+        assert_eq!(
+            order(DatabaseType::PostgreSQL, "partitioned table"),
+            "tableoid, ctid"
+        );
+        assert_eq!(order(DatabaseType::PostgreSQL, "view"), "ROW(page.*)::TEXT");
+        assert_eq!(order(DatabaseType::SQLite, "table"), "rowid");
+        assert_eq!(order(DatabaseType::SQLite, "view"), r#""id", "amount""#);
+        assert_eq!(order(DatabaseType::MySQL, "table"), "`id`, `amount`");
+    }
+
+    #[test]
+    fn test_quote_identifier_escapes_quotes() {
+        assert_eq!(
+            quote_identifier(DatabaseType::SQLite, r#"odd"name"#),
+            r#""odd""name""#
+        );
+        assert_eq!(quote_identifier(DatabaseType::MySQL, "a`b"), "`a``b`");
+    }
+}
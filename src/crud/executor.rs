@@ -1,137 +1,331 @@
-use super::postgres::PostgresExecutor;
+use super::session::QuerySession;
+use crate::database::connector::DatabaseType;
 use crate::database::pool::DbPool;
 use crate::layout::data_table::DynamicData;
-use crate::state::update_query_stats;
-use crate::utils::query_timer::query_timer;
-use crate::utils::query_type::Query;
-use async_trait::async_trait;
-use sqlx::{Column, Row};
-use std::time::Duration;
+use crate::utils::query_type::{Query, QueryKind};
+use futures::future::BoxFuture;
+use futures::stream::{self, BoxStream};
+use futures::{StreamExt, TryStreamExt};
+use sqlx::{Column, Either, Row};
+use std::time::{Duration, Instant};
+
+/// Rows are handed on at least this often while a result streams in, so
+/// the first page shows before the whole result has arrived.
+const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+/// Most rows handed to a `RowSink` at once.
+const BATCH_ROWS: usize = 1000;
 
 #[allow(dead_code)]
 pub struct DataMeta {
     pub rows: usize,
     pub message: String,
+    pub elapsed: Duration,
+    /// Whether the statement was stopped at the limit of the `RowSink`.
+    pub truncated: bool,
 }
 
 #[allow(dead_code)]
 pub enum ExecutionResult {
-    Affected { rows: usize, message: String },
-    Data(DynamicData, DataMeta),
+    Affected {
+        rows: usize,
+        message: String,
+        elapsed: Duration,
+    },
+    /// A result set, whose rows went to the `RowSink`.
+    Data(DataMeta),
 }
 
-#[async_trait]
-pub trait DatabaseExecutor: Send + Sync {
-    type Row: Row + Send + Sync;
+/// What the driver sends for a statement, as it arrives: affected-row
+/// counts and rows.
+pub type OutputStream<'c, R> = BoxStream<'c, Result<Either<u64, R>, sqlx::Error>>;
+
+pub trait DatabaseExecutor: Send {
+    type Row: Row + Send;
+    /// Engine the statements are written for.
+    const DB_TYPE: DatabaseType;
+
+    /// Starts one statement of any kind.
+    fn run<'c>(&'c mut self, query: &'c str) -> OutputStream<'c, Self::Row>;
+    /// Starts `query` again as plain text after `run` failed with `err`
+    /// because the server cannot prepare it, or returns `None` for any
+    /// other error.
+    fn run_unprepared<'c>(
+        &'c mut self,
+        _query: &'c str,
+        _err: &sqlx::Error,
+    ) -> Option<OutputStream<'c, Self::Row>> {
+        None
+    }
+    /// Waits for the connection to be ready for the next statement.
+    fn ping(&mut self) -> BoxFuture<'_, Result<(), sqlx::Error>>;
+    fn get_value_as_string(row: &Self::Row, index: usize) -> String;
+}
 
-    async fn fetch(&self, query: &str) -> Result<Vec<Self::Row>, sqlx::Error>;
-    async fn insert(&self, query: &str) -> Result<u64, sqlx::Error>;
-    async fn update(&self, query: &str) -> Result<u64, sqlx::Error>;
-    async fn delete(&self, query: &str) -> Result<u64, sqlx::Error>;
-    fn get_value_as_string(&self, row: &Self::Row, index: usize) -> String;
+/// Takes the rows of result sets while they stream in.
+pub trait RowSink: Send {
+    /// Rows of a result set to take. The statement is stopped once more
+    /// rows than that arrive, leaving the result truncated.
+    fn limit(&self) -> usize;
+    /// Rows at the start of a result set to pass over, when fetching more
+    /// of a result by running its statement again.
+    fn skip(&self) -> usize {
+        0
+    }
+    /// Takes the next batch of a result set. Each result set gets at least
+    /// one batch, so an empty result still delivers its headers.
+    fn rows(&mut self, batch: DynamicData);
 }
 
-pub fn create_executor(pool: &DbPool) -> impl DatabaseExecutor {
-    match pool {
-        DbPool::Postgres(pg_pool) => PostgresExecutor::new(pg_pool.clone()),
-        DbPool::MySQL(_) => todo!(),
-        DbPool::SQLite(_) => todo!(),
+/// Keeps every row of a single result set.
+#[derive(Default)]
+pub struct CollectRows(pub Option<DynamicData>);
+
+impl RowSink for CollectRows {
+    fn limit(&self) -> usize {
+        usize::MAX
+    }
+
+    fn rows(&mut self, batch: DynamicData) {
+        match &mut self.0 {
+            Some(data) => data.extend(batch),
+            None => self.0 = Some(batch),
+        }
     }
 }
 
-fn format_affected_result(query_type: &str, rows: usize, elapsed: Duration) -> ExecutionResult {
+fn format_affected_result(query: &Query, rows: usize, elapsed: Duration) -> ExecutionResult {
+    let summary = match query.kind {
+        QueryKind::Write => format!("{} {} rows affected.", query.keyword, rows),
+        _ => format!("{} completed successfully.", query.keyword),
+    };
     let message = format!(
-        "{} {} rows affected.\nQuery completed in {} msec.",
-        query_type,
-        rows,
+        "{}\nQuery completed in {} msec.",
+        summary,
         elapsed.as_millis()
     );
-    ExecutionResult::Affected { rows, message }
+    ExecutionResult::Affected {
+        rows,
+        message,
+        elapsed,
+    }
+}
+
+/// Runs one statement on a connection of its own from `pool` and collects
+/// all of its rows, which are empty unless it produced a result set.
+pub async fn execute_query(pool: &DbPool, sql: &str) -> Result<DynamicData, sqlx::Error> {
+    let mut rows = CollectRows::default();
+    QuerySession::open(pool, None)
+        .await?
+        .execute(sql, &mut rows)
+        .await?;
+    Ok(rows.0.unwrap_or_else(|| DynamicData::new(vec![], vec![])))
 }
 
-async fn run_affected_query<Fut>(
-    fut: Fut,
-    query_type: &'static str,
+/// Runs any statement. Whatever produces rows is a result set, as is a
+/// query that came back empty; everything else reports affected rows.
+///
+/// Rows go to `sink` in batches as they arrive, up to its limit. A read-only
+/// result cut short there is left unfinished on the connection; any other
+/// statement is read to the end without keeping the rest, so that it completes.
+pub async fn run_query<E>(
+    executor: &mut E,
+    sql: &str,
+    sink: &mut dyn RowSink,
 ) -> Result<ExecutionResult, sqlx::Error>
 where
-    Fut: std::future::Future<Output = Result<u64, sqlx::Error>>,
+    E: DatabaseExecutor,
 {
-    let (result, elapsed) = query_timer(fut).await;
-    let rows = result? as usize;
-    update_query_stats(rows, elapsed).await;
-    Ok(format_affected_result(query_type, rows, elapsed))
-}
+    let query = Query::from_sql(sql, E::DB_TYPE);
+    let started = Instant::now();
+    let mut stream = executor.run(sql);
+    let first = match stream.try_next().await {
+        Err(err) => {
+            drop(stream);
+            stream = executor.run_unprepared(sql, &err).ok_or(err)?;
+            stream.try_next().await
+        }
+        first => first,
+    };
+    let mut stream = stream::iter(first.transpose()).chain(stream);
+
+    let mut rows_affected = 0;
+    let mut headers: Option<Vec<String>> = None;
+    let mut batch: Vec<Vec<String>> = Vec::new();
+    let mut skipped = 0;
+    let mut fetched = 0;
+    let limit = sink.limit();
+    let mut truncated = false;
+    let mut last_flush = Instant::now();
 
-pub async fn execute_query(pool: &DbPool, sql: &str) -> Result<ExecutionResult, sqlx::Error> {
-    let executor = create_executor(pool);
-
-    match Query::from_sql(sql) {
-        Query::SELECT => {
-            let (rows_result, elapsed) = query_timer(executor.fetch(sql)).await;
-            let rows = rows_result?;
-            let row_count = rows.len();
-
-            update_query_stats(row_count, elapsed).await;
-
-            let message = format!(
-                "Successfully run. Total query runtime: {} ms.\n{} rows fetched.",
-                elapsed.as_millis(),
-                row_count,
-            );
-
-            let (headers, row_data, column_widths) = process_rows(&rows, &executor);
-
-            Ok(ExecutionResult::Data(
-                DynamicData {
-                    headers,
-                    rows: row_data,
-                    column_widths: column_widths.clone(),
-                    min_column_widths: column_widths,
-                },
-                DataMeta {
-                    rows: row_count,
-                    message,
-                },
-            ))
+    while let Some(item) = stream.try_next().await? {
+        let row = match item {
+            Either::Left(rows) => {
+                rows_affected += rows;
+                continue;
+            }
+            Either::Right(row) => row,
+        };
+        let headers = headers
+            .get_or_insert_with(|| row.columns().iter().map(|c| c.name().to_string()).collect());
+        if skipped < sink.skip() {
+            skipped += 1;
+            continue;
+        }
+        if fetched == limit {
+            truncated = true;
+            if query.read_only {
+                break;
+            }
+            continue;
         }
 
-        Query::INSERT => run_affected_query(executor.insert(sql), "INSERT").await,
-        Query::UPDATE => run_affected_query(executor.update(sql), "UPDATE").await,
-        Query::DELETE => run_affected_query(executor.delete(sql), "DELETE").await,
+        batch.push(
+            (0..headers.len())
+                .map(|i| E::get_value_as_string(&row, i))
+                .collect(),
+        );
+        fetched += 1;
 
-        Query::UNKNOWN => Err(sqlx::Error::Protocol("Unsupported query".into())),
+        if fetched == limit || batch.len() >= BATCH_ROWS || last_flush.elapsed() >= FLUSH_INTERVAL {
+            sink.rows(DynamicData::new(
+                headers.clone(),
+                std::mem::take(&mut batch),
+            ));
+            last_flush = Instant::now();
+        }
     }
+    drop(stream);
+    let elapsed = started.elapsed();
+
+    let Some(headers) = headers else {
+        if !query.returns_rows {
+            let rows = rows_affected as usize;
+            return Ok(format_affected_result(&query, rows, elapsed));
+        }
+        sink.rows(DynamicData::new(vec![], vec![]));
+        return Ok(ExecutionResult::Data(DataMeta {
+            rows: 0,
+            message: fetched_message(0, elapsed, false, true),
+            elapsed,
+            truncated: false,
+        }));
+    };
+    if !batch.is_empty() {
+        sink.rows(DynamicData::new(headers, batch));
+    }
+
+    Ok(ExecutionResult::Data(DataMeta {
+        rows: fetched,
+        message: fetched_message(fetched, elapsed, truncated, query.read_only),
+        elapsed,
+        truncated,
+    }))
 }
 
-fn process_rows<R, E>(rows: &[R], executor: &E) -> (Vec<String>, Vec<Vec<String>>, Vec<u16>)
-where
-    R: Row,
-    E: DatabaseExecutor<Row = R>,
-{
-    let mut headers: Vec<String> = Vec::new();
-    let mut column_widths = Vec::new();
-    let mut data_rows = Vec::new();
-
-    if let Some(first_row) = rows.first() {
-        let cols = first_row.columns();
-        headers = cols.iter().map(|c| c.name().to_string()).collect();
-        column_widths = headers.iter().map(|h| h.len() as u16).collect();
+fn fetched_message(rows: usize, elapsed: Duration, truncated: bool, stopped: bool) -> String {
+    let mut message = format!(
+        "Successfully run. Total query runtime: {} ms.\n{} rows fetched.",
+        elapsed.as_millis(),
+        rows,
+    );
+    if truncated && stopped {
+        message.push_str(" Stopped at the row limit, the rest was not fetched.");
+    } else if truncated {
+        message.push_str(" Kept up to the row limit, the statement ran to completion.");
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::pool::sqlite_test_pool;
+
+    /// Takes 3 rows after passing over `skip`.
+    #[derive(Default)]
+    struct Capped {
+        skip: usize,
+        batches: Vec<DynamicData>,
     }
 
-    for row in rows {
-        let mut data_row = Vec::with_capacity(headers.len());
+    impl RowSink for Capped {
+        fn limit(&self) -> usize {
+            3
+        }
 
-        for (i, col_width) in column_widths.iter_mut().take(headers.len()).enumerate() {
-            let val = executor.get_value_as_string(row, i);
-            let val_len = val.len() as u16;
+        fn skip(&self) -> usize {
+            self.skip
+        }
 
-            if val_len > *col_width {
-                *col_width = val_len;
+        fn rows(&mut self, batch: DynamicData) {
+            self.batches.push(batch);
+        }
+    }
+
+    impl Capped {
+        fn values(self) -> Vec<String> {
+            let mut data = DynamicData::new(vec![], vec![]);
+            for batch in self.batches {
+                assert_eq!(batch.headers, ["n"]);
+                data.extend(batch);
             }
-            data_row.push(val);
+            data.rows.into_iter().map(|row| row[0].clone()).collect()
         }
-        data_rows.push(data_row);
     }
 
-    (headers, data_rows, column_widths)
+    #[tokio::test]
+    async fn test_rows_stream_up_to_the_limit() {
+        let pool = sqlite_test_pool("").await;
+        let mut session = QuerySession::open(&pool, None).await.unwrap();
+        let sql = "WITH RECURSIVE t(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM t WHERE n < 10) \
+                   SELECT n FROM t";
+
+        let mut sink = Capped::default();
+        let Ok(ExecutionResult::Data(meta)) = session.execute(sql, &mut sink).await else {
+            panic!("expected a result set");
+        };
+        assert_eq!(meta.rows, 3);
+        assert!(meta.truncated);
+        assert!(meta.message.contains("Stopped at the row limit"));
+        assert_eq!(sink.values(), ["1", "2", "3"]);
+
+        // Running it again picks up after the rows already taken.
+        let mut sink = Capped {
+            skip: 8,
+            ..Capped::default()
+        };
+        let Ok(ExecutionResult::Data(meta)) = session.execute(sql, &mut sink).await else {
+            panic!("expected a result set");
+        };
+        assert!(!meta.truncated);
+        assert_eq!(sink.values(), ["9", "10"]);
+
+        // An empty result still reaches the sink.
+        let mut rows = CollectRows::default();
+        let result = session.execute("SELECT 1 AS one WHERE 0", &mut rows).await;
+        assert!(matches!(result, Ok(ExecutionResult::Data(_))));
+        assert!(rows.0.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_statements_that_change_data_run_past_the_limit() {
+        let pool = sqlite_test_pool("CREATE TABLE t (n INTEGER)").await;
+        let mut session = QuerySession::open(&pool, None).await.unwrap();
+
+        let mut sink = Capped::default();
+        let sql = "INSERT INTO t VALUES (1), (2), (3), (4), (5) RETURNING n";
+        let Ok(ExecutionResult::Data(meta)) = session.execute(sql, &mut sink).await else {
+            panic!("expected a result set");
+        };
+        assert!(meta.truncated);
+        assert!(meta.message.contains("ran to completion"));
+        assert_eq!(sink.values(), ["1", "2", "3"]);
+
+        let mut rows = CollectRows::default();
+        session
+            .execute("SELECT count(*) AS n FROM t", &mut rows)
+            .await
+            .unwrap();
+        assert_eq!(rows.0.unwrap().rows, [["5"]]);
+    }
 }
@@ -1,2 +1,6 @@
+pub mod browse;
 pub mod executor;
+pub mod mysql;
 pub mod postgres;
+pub mod session;
+pub mod sqlite;
@@ -0,0 +1,109 @@
+use super::executor::{DatabaseExecutor, OutputStream};
+use crate::database::connector::DatabaseType;
+use futures::TryStreamExt;
+use futures::future::BoxFuture;
+use hex;
+use serde_json::Value;
+use sqlx::{
+    Connection, Executor, MySql, Row, ValueRef,
+    mysql::{MySqlDatabaseError, MySqlRow},
+    pool::PoolConnection,
+    types::{Json, chrono},
+};
+
+/// "This command is not supported in the prepared statement protocol yet".
+const ER_UNSUPPORTED_PS: u16 = 1295;
+
+pub struct MySqlExecutor {
+    conn: PoolConnection<MySql>,
+}
+
+impl MySqlExecutor {
+    pub fn new(conn: PoolConnection<MySql>) -> Self {
+        Self { conn }
+    }
+}
+
+impl DatabaseExecutor for MySqlExecutor {
+    type Row = MySqlRow;
+    const DB_TYPE: DatabaseType = DatabaseType::MySQL;
+
+    fn run<'c>(&'c mut self, query: &'c str) -> OutputStream<'c, MySqlRow> {
+        Box::pin(
+            self.conn
+                .fetch_many(sqlx::query(query))
+                .map_ok(|item| item.map_left(|result| result.rows_affected())),
+        )
+    }
+
+    /// Statements such as CREATE TRIGGER cannot be prepared; those are sent
+    /// as plain text instead.
+    fn run_unprepared<'c>(
+        &'c mut self,
+        query: &'c str,
+        err: &sqlx::Error,
+    ) -> Option<OutputStream<'c, MySqlRow>> {
+        let sqlx::Error::Database(err) = err else {
+            return None;
+        };
+        err.try_downcast_ref::<MySqlDatabaseError>()
+            .filter(|err| err.number() == ER_UNSUPPORTED_PS)?;
+        Some(Box::pin(self.conn.fetch_many(sqlx::raw_sql(query)).map_ok(
+            |item| item.map_left(|result| result.rows_affected()),
+        )))
+    }
+
+    fn ping(&mut self) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+        self.conn.ping()
+    }
+
+    fn get_value_as_string(row: &MySqlRow, index: usize) -> String {
+        if row.try_get_raw(index).is_ok_and(|v| v.is_null()) {
+            return "[null]".to_string();
+        }
+
+        macro_rules! try_get_string {
+            ($($typ:ty),*) => {
+                $(
+                    if let Ok(val) = row.try_get::<$typ, _>(index) {
+                        return val.to_string();
+                    }
+                )*
+            };
+        }
+
+        // Signed and unsigned integers are checked before `bool`, which MySQL
+        // treats as compatible with every integer column.
+        try_get_string!(
+            String,
+            i64,
+            u64,
+            f64,
+            f32,
+            bool,
+            chrono::NaiveDate,
+            chrono::NaiveDateTime,
+            chrono::NaiveTime,
+            chrono::DateTime<chrono::Utc>
+        );
+
+        if let Ok(Json(val)) = row.try_get::<Json<Value>, _>(index) {
+            return match serde_json::to_string(&val) {
+                Ok(s) => s,
+                Err(e) => format!("[json-error: {}]", e),
+            };
+        }
+
+        if let Ok(val) = row.try_get::<Vec<u8>, _>(index) {
+            return hex::encode(val);
+        }
+
+        // DECIMAL and other types without a native Rust mapping are sent as
+        // text by the server, so decode them without the compatibility check.
+        if let Ok(val) = row.try_get_unchecked::<String, _>(index) {
+            return val;
+        }
+
+        "[null]".to_string()
+    }
+}
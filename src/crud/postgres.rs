@@ -1,52 +1,43 @@
-use super::executor::DatabaseExecutor;
-use async_trait::async_trait;
+use super::executor::{DatabaseExecutor, OutputStream};
+use crate::database::connector::DatabaseType;
+use futures::TryStreamExt;
+use futures::future::BoxFuture;
 use hex;
 use serde_json::Value;
 use sqlx::{
-    PgPool, Row,
+    Connection, Executor, Postgres, Row,
+    pool::PoolConnection,
     postgres::PgRow,
     types::{Json, Uuid, chrono},
 };
 
 pub struct PostgresExecutor {
-    pool: PgPool,
+    conn: PoolConnection<Postgres>,
 }
 
 impl PostgresExecutor {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
-    }
-
-    async fn execute_query(&self, query: &str) -> Result<u64, sqlx::Error> {
-        Ok(sqlx::query(query)
-            .execute(&self.pool)
-            .await?
-            .rows_affected())
+    pub fn new(conn: PoolConnection<Postgres>) -> Self {
+        Self { conn }
     }
 }
 
-#[async_trait]
 impl DatabaseExecutor for PostgresExecutor {
     type Row = PgRow;
+    const DB_TYPE: DatabaseType = DatabaseType::PostgreSQL;
 
-    async fn fetch(&self, query: &str) -> Result<Vec<PgRow>, sqlx::Error> {
-        let rows = sqlx::query(query).fetch_all(&self.pool).await?;
-        Ok(rows)
-    }
-
-    async fn insert(&self, query: &str) -> Result<u64, sqlx::Error> {
-        self.execute_query(query).await
-    }
-
-    async fn update(&self, query: &str) -> Result<u64, sqlx::Error> {
-        self.execute_query(query).await
+    fn run<'c>(&'c mut self, query: &'c str) -> OutputStream<'c, PgRow> {
+        Box::pin(
+            self.conn
+                .fetch_many(sqlx::query(query))
+                .map_ok(|item| item.map_left(|result| result.rows_affected())),
+        )
     }
 
-    async fn delete(&self, query: &str) -> Result<u64, sqlx::Error> {
-        self.execute_query(query).await
+    fn ping(&mut self) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+        self.conn.ping()
     }
 
-    fn get_value_as_string(&self, row: &PgRow, index: usize) -> String {
+    fn get_value_as_string(row: &PgRow, index: usize) -> String {
         macro_rules! try_get_string {
             ($($typ:ty),*) => {
                 $(
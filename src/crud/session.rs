@@ -0,0 +1,281 @@
+use super::executor::{DataMeta, DatabaseExecutor, ExecutionResult, RowSink, run_query};
+use super::mysql::MySqlExecutor;
+use super::postgres::PostgresExecutor;
+use super::sqlite::SqliteExecutor;
+use crate::database::connector::DatabaseType;
+use crate::database::pool::DbPool;
+use crate::utils::query_type::Query;
+use sqlx::{MySqlPool, PgPool};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Mutex;
+
+/// Virtual machine instructions SQLite runs between checks for a cancel.
+const SQLITE_PROGRESS_OPS: i32 = 1000;
+
+/// Stops the statements of a `QuerySession` from another task. Cancelling
+/// before the session is open keeps it from running anything.
+#[derive(Clone, Default)]
+pub struct CancelHandle(Arc<CancelState>);
+
+#[derive(Default)]
+struct CancelState {
+    requested: AtomicBool,
+    /// How to reach the server while a session is open. Held while a
+    /// cancel is sent, so the session cannot let go of its connection then.
+    target: Mutex<Option<CancelTarget>>,
+}
+
+enum CancelTarget {
+    /// Backend process of the session, for `pg_cancel_backend`.
+    Postgres { pool: PgPool, pid: i32 },
+    /// Connection id of the session, for `KILL QUERY`.
+    MySQL { pool: MySqlPool, id: u64 },
+    /// Read by the progress handler of the session's connection, which
+    /// interrupts the statement once it is set.
+    SQLite(Arc<AtomicBool>),
+}
+
+impl CancelHandle {
+    pub fn is_cancelled(&self) -> bool {
+        self.0.requested.load(Ordering::SeqCst)
+    }
+
+    /// Cancels the statement running on the server, if any, and every
+    /// statement after it.
+    pub async fn cancel(&self) -> Result<(), sqlx::Error> {
+        self.0.requested.store(true, Ordering::SeqCst);
+        let target = self.0.target.lock().await;
+        match &*target {
+            Some(CancelTarget::SQLite(interrupt)) => interrupt.store(true, Ordering::SeqCst),
+            Some(target) => target.cancel_on_server().await?,
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Cancels the statement running on the server, if any, but not the
+    /// statements after it. A SQLite statement stops once it is dropped.
+    async fn cancel_statement(&self) -> Result<(), sqlx::Error> {
+        match &*self.0.target.lock().await {
+            Some(CancelTarget::SQLite(_)) | None => Ok(()),
+            Some(target) => target.cancel_on_server().await,
+        }
+    }
+
+    /// Forgets the session once it is over, so its pooled connection is
+    /// not cancelled while running someone else's query. Waits for a cancel
+    /// being sent to get there first.
+    async fn disarm(&self) {
+        disarm(&mut *self.0.target.lock().await);
+    }
+}
+
+impl CancelTarget {
+    async fn cancel_on_server(&self) -> Result<(), sqlx::Error> {
+        match self {
+            CancelTarget::Postgres { pool, pid } => {
+                sqlx::query("SELECT pg_cancel_backend($1)")
+                    .bind(pid)
+                    .execute(pool)
+                    .await?;
+            }
+            CancelTarget::MySQL { pool, id } => {
+                sqlx::raw_sql(&format!("KILL QUERY {}", id))
+                    .execute(pool)
+                    .await?;
+            }
+            CancelTarget::SQLite(_) => {}
+        }
+        Ok(())
+    }
+}
+
+fn disarm(target: &mut Option<CancelTarget>) {
+    if let Some(CancelTarget::SQLite(interrupt)) = target.take() {
+        interrupt.store(false, Ordering::SeqCst);
+    }
+}
+
+enum SessionExecutor {
+    Postgres(PostgresExecutor),
+    MySQL(MySqlExecutor),
+    SQLite(SqliteExecutor),
+}
+
+/// One pooled connection held for a run of statements, so they share
+/// session state such as `SET` and temporary tables. A session that can be
+/// cancelled is to be ended with `close`.
+pub struct QuerySession {
+    executor: SessionExecutor,
+    cancel: Option<CancelHandle>,
+}
+
+impl QuerySession {
+    /// Takes a connection from `pool`. With `cancel`, the statements of the
+    /// session can be cancelled on the server until the session is closed.
+    pub async fn open(pool: &DbPool, cancel: Option<CancelHandle>) -> Result<Self, sqlx::Error> {
+        let (executor, target) = match pool {
+            DbPool::Postgres(pg) => {
+                let mut conn = pg.acquire().await?;
+                let target = match cancel {
+                    Some(_) => Some(CancelTarget::Postgres {
+                        pool: pg.clone(),
+                        pid: sqlx::query_scalar("SELECT pg_backend_pid()")
+                            .fetch_one(&mut *conn)
+                            .await?,
+                    }),
+                    None => None,
+                };
+                (
+                    SessionExecutor::Postgres(PostgresExecutor::new(conn)),
+                    target,
+                )
+            }
+            DbPool::MySQL(mysql) => {
+                let mut conn = mysql.acquire().await?;
+                let target = match cancel {
+                    Some(_) => Some(CancelTarget::MySQL {
+                        pool: mysql.clone(),
+                        id: sqlx::query_scalar("SELECT CONNECTION_ID()")
+                            .fetch_one(&mut *conn)
+                            .await?,
+                    }),
+                    None => None,
+                };
+                (SessionExecutor::MySQL(MySqlExecutor::new(conn)), target)
+            }
+            DbPool::SQLite(sqlite) => {
+                let mut conn = sqlite.acquire().await?;
+                let target = match cancel {
+                    Some(_) => {
+                        let interrupt = Arc::new(AtomicBool::new(false));
+                        let flag = interrupt.clone();
+                        // Returning false from the handler interrupts the
+                        // statement. It stays installed on the pooled
+                        // connection, reading a flag that is cleared again.
+                        conn.lock_handle()
+                            .await?
+                            .set_progress_handler(SQLITE_PROGRESS_OPS, move || {
+                                !flag.load(Ordering::SeqCst)
+                            });
+                        Some(CancelTarget::SQLite(interrupt))
+                    }
+                    None => None,
+                };
+                (SessionExecutor::SQLite(SqliteExecutor::new(conn)), target)
+            }
+        };
+
+        if let Some(cancel) = &cancel {
+            *cancel.0.target.lock().await = target;
+        }
+        Ok(Self { executor, cancel })
+    }
+
+    /// Runs one statement, streaming any rows it returns to `sink`. A
+    /// read-only statement stopped at the limit of `sink` is cancelled on the
+    /// server rather than left to send the rest of its rows.
+    pub async fn execute(
+        &mut self,
+        sql: &str,
+        sink: &mut dyn RowSink,
+    ) -> Result<ExecutionResult, sqlx::Error> {
+        let result = match &mut self.executor {
+            SessionExecutor::Postgres(executor) => run_query(executor, sql, sink).await?,
+            SessionExecutor::MySQL(executor) => run_query(executor, sql, sink).await?,
+            SessionExecutor::SQLite(executor) => run_query(executor, sql, sink).await?,
+        };
+        if let ExecutionResult::Data(DataMeta {
+            truncated: true, ..
+        }) = result
+            && Query::from_sql(sql, self.db_type()).read_only
+        {
+            if let Some(cancel) = &self.cancel {
+                cancel.cancel_statement().await?;
+            }
+            // The connection reads past what the statement still sent. One
+            // that was cancelled then reports so once.
+            if self.ping().await.is_err() {
+                self.ping().await?;
+            }
+        }
+        Ok(result)
+    }
+
+    fn db_type(&self) -> DatabaseType {
+        match &self.executor {
+            SessionExecutor::Postgres(_) => PostgresExecutor::DB_TYPE,
+            SessionExecutor::MySQL(_) => MySqlExecutor::DB_TYPE,
+            SessionExecutor::SQLite(_) => SqliteExecutor::DB_TYPE,
+        }
+    }
+
+    async fn ping(&mut self) -> Result<(), sqlx::Error> {
+        match &mut self.executor {
+            SessionExecutor::Postgres(executor) => executor.ping().await,
+            SessionExecutor::MySQL(executor) => executor.ping().await,
+            SessionExecutor::SQLite(executor) => executor.ping().await,
+        }
+    }
+
+    /// Ends the session once no cancel is on its way to its connection,
+    /// which then goes back to the pool.
+    pub async fn close(mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            cancel.disarm().await;
+        }
+    }
+}
+
+impl Drop for QuerySession {
+    /// Disarms a session that was not closed, unless a cancel is being sent
+    /// right then.
+    fn drop(&mut self) {
+        if let Some(cancel) = &self.cancel
+            && let Ok(mut target) = cancel.0.target.try_lock()
+        {
+            disarm(&mut target);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crud::executor::CollectRows;
+    use crate::database::pool::sqlite_test_pool;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancel_interrupts_sqlite_and_frees_the_connection() {
+        let pool = sqlite_test_pool("").await;
+        let cancel = CancelHandle::default();
+        let mut session = QuerySession::open(&pool, Some(cancel.clone()))
+            .await
+            .unwrap();
+
+        let endless = "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n) \
+                       SELECT COUNT(*) FROM n";
+        let canceller = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            cancel.cancel().await.unwrap();
+        });
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            session.execute(endless, &mut CollectRows::default()),
+        )
+        .await
+        .expect("the query was not interrupted");
+        assert!(result.is_err());
+        canceller.await.unwrap();
+        session.close().await;
+
+        // The same pooled connection runs queries again.
+        let mut session = QuerySession::open(&pool, None).await.unwrap();
+        let result = session
+            .execute("SELECT 1", &mut CollectRows::default())
+            .await;
+        assert!(result.is_ok());
+    }
+}
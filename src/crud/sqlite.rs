@@ -0,0 +1,58 @@
+use super::executor::{DatabaseExecutor, OutputStream};
+use crate::database::connector::DatabaseType;
+use futures::TryStreamExt;
+use futures::future::BoxFuture;
+use hex;
+use sqlx::{
+    Connection, Executor, Row, Sqlite, TypeInfo, ValueRef, pool::PoolConnection, sqlite::SqliteRow,
+};
+
+pub struct SqliteExecutor {
+    conn: PoolConnection<Sqlite>,
+}
+
+impl SqliteExecutor {
+    pub fn new(conn: PoolConnection<Sqlite>) -> Self {
+        Self { conn }
+    }
+}
+
+impl DatabaseExecutor for SqliteExecutor {
+    type Row = SqliteRow;
+    const DB_TYPE: DatabaseType = DatabaseType::SQLite;
+
+    fn run<'c>(&'c mut self, query: &'c str) -> OutputStream<'c, SqliteRow> {
+        Box::pin(
+            self.conn
+                .fetch_many(sqlx::query(query))
+                .map_ok(|item| item.map_left(|result| result.rows_affected())),
+        )
+    }
+
+    fn ping(&mut self) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+        self.conn.ping()
+    }
+
+    fn get_value_as_string(row: &SqliteRow, index: usize) -> String {
+        // SQLite is dynamically typed: the declared column type says nothing
+        // reliable about a value, so dispatch on the storage class instead.
+        let storage_class = match row.try_get_raw(index) {
+            Ok(value) if value.is_null() => return "[null]".to_string(),
+            Ok(value) => value.type_info().name().to_string(),
+            Err(_) => return "[null]".to_string(),
+        };
+
+        let value = match storage_class.as_str() {
+            "INTEGER" => row
+                .try_get_unchecked::<i64, _>(index)
+                .map(|v| v.to_string()),
+            "REAL" => row
+                .try_get_unchecked::<f64, _>(index)
+                .map(|v| v.to_string()),
+            "BLOB" => row.try_get_unchecked::<Vec<u8>, _>(index).map(hex::encode),
+            _ => row.try_get_unchecked::<String, _>(index),
+        };
+
+        value.unwrap_or_else(|_| "[null]".to_string())
+    }
+}
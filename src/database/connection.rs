@@ -0,0 +1,188 @@
+use super::connector::{ConnectionDetails, DatabaseType};
+use super::fetch::{
+    EXTENSIONS_GROUP, MATERIALIZED_VIEWS_GROUP, ObjectKind, SchemaObject, TABLES_GROUP,
+    TableMetadata, TreeFilter, VIEWS_GROUP, metadata_to_tree_items,
+};
+use super::pool::DbPool;
+use ratatui::text::Text;
+use tui_tree_widget::TreeItem;
+
+/// Identifier of the hint shown under a connection with nothing to list.
+pub const EMPTY_HINT: &str = ":empty";
+
+/// An open database connection shown as a root node in the sidebar.
+#[derive(Debug, Clone)]
+pub struct Connection {
+    pub name: String,
+    pub details: ConnectionDetails,
+    pub pool: DbPool,
+    pub metadata: Vec<TableMetadata>,
+    pub objects: Vec<SchemaObject>,
+}
+
+impl Connection {
+    pub fn new(
+        name: String,
+        details: ConnectionDetails,
+        pool: DbPool,
+        metadata: Vec<TableMetadata>,
+        objects: Vec<SchemaObject>,
+    ) -> Self {
+        Self {
+            name,
+            details,
+            pool,
+            metadata,
+            objects,
+        }
+    }
+
+    /// Looks up a table by the schema and name used in its sidebar path.
+    pub fn table_mut(&mut self, schema: &str, name: &str) -> Option<&mut TableMetadata> {
+        self.metadata
+            .iter_mut()
+            .find(|table| table.schema == schema && table.name == name)
+    }
+
+    /// Finds the object at a `[connection, schema, group, identity]` sidebar
+    /// path, or `[connection, extensions group, identity]` for an extension.
+    pub fn object_at(&self, path: &[String]) -> Option<&SchemaObject> {
+        match path {
+            [_, group, identity] if group == EXTENSIONS_GROUP => self
+                .objects
+                .iter()
+                .find(|o| o.kind == ObjectKind::Extension && &o.identity == identity),
+            [_, schema, group, identity] if table_path(path).is_none() => {
+                self.objects.iter().find(|o| {
+                    &o.schema == schema && o.kind.group().0 == group && &o.identity == identity
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Builds the connection's sidebar node, or `None` when nothing in it
+    /// matches an active filter.
+    pub fn to_tree_item(
+        &self,
+        active: bool,
+        filter: &mut TreeFilter,
+    ) -> Option<TreeItem<'static, String>> {
+        let marker = if active { "●" } else { "○" };
+        let label = format!(
+            "{} {} [{}] ({} table{})",
+            marker,
+            self.name,
+            self.details.db_type,
+            self.metadata.len(),
+            if self.metadata.len() == 1 { "" } else { "s" }
+        );
+
+        let mut children = metadata_to_tree_items(
+            &self.metadata,
+            &self.objects,
+            std::slice::from_ref(&self.name),
+            filter,
+        );
+        if children.is_empty() {
+            if filter.is_active() {
+                return None;
+            }
+            children.push(TreeItem::new_leaf(
+                EMPTY_HINT.to_string(),
+                "💡 No tables yet, create one in the editor".to_string(),
+            ));
+        }
+
+        let item = TreeItem::new(self.name.clone(), Text::from(label), children)
+            .expect("table names are unique within a connection");
+        Some(item)
+    }
+}
+
+/// Splits the sidebar path of a table or view,
+/// `[connection, schema, group, table]`, into its connection, schema and name.
+pub fn table_path(path: &[String]) -> Option<(&str, &str, &str)> {
+    match path {
+        [connection, schema, group, table]
+            if [TABLES_GROUP, VIEWS_GROUP, MATERIALIZED_VIEWS_GROUP].contains(&group.as_str()) =>
+        {
+            Some((connection, schema, table))
+        }
+        _ => None,
+    }
+}
+
+/// Suggests a display name for a connection, e.g. `app@db.internal` or `app.db`.
+pub fn default_connection_name(details: &ConnectionDetails) -> String {
+    match details.db_type {
+        DatabaseType::SQLite => {
+            let path = details.file_path.as_deref().unwrap_or("sqlite");
+            std::path::Path::new(path)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.to_string())
+        }
+        _ => format!(
+            "{}@{}",
+            details.database.as_deref().unwrap_or("default"),
+            details.host.as_deref().unwrap_or("localhost")
+        ),
+    }
+}
+
+/// Appends ` (2)`, ` (3)`, ... until `name` does not clash with `existing`.
+pub fn unique_connection_name(name: String, existing: &[Connection]) -> String {
+    let taken = |candidate: &str| existing.iter().any(|c| c.name == candidate);
+    if !taken(&name) {
+        return name;
+    }
+
+    (2..)
+        .map(|n| format!("{name} ({n})"))
+        .find(|candidate| !taken(candidate))
+        .expect("an unused suffix always exists")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_connection_name() {
+        let mut details =
+            ConnectionDetails::from_url("postgres://app@db.internal/billing").unwrap();
+        assert_eq!(default_connection_name(&details), "billing@db.internal");
+
+        details.database = None;
+        details.host = None;
+        assert_eq!(default_connection_name(&details), "default@localhost");
+
+        let sqlite = ConnectionDetails::sqlite("./fixtures/app.db");
+        assert_eq!(default_connection_name(&sqlite), "app.db");
+    }
+
+    #[test]
+    fn test_table_path_only_matches_table_groups() {
+        let path = |parts: &[&str]| parts.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            table_path(&path(&["app", "billing", TABLES_GROUP, "invoices"])),
+            Some(("app", "billing", "invoices"))
+        );
+        assert_eq!(
+            table_path(&path(&["app", "public", VIEWS_GROUP, "active_users"])),
+            Some(("app", "public", "active_users"))
+        );
+        assert_eq!(
+            table_path(&path(&[
+                "app",
+                "public",
+                "functions",
+                "add(integer,integer)"
+            ])),
+            None
+        );
+        assert_eq!(table_path(&path(&["app", "public"])), None);
+    }
+}
@@ -0,0 +1,255 @@
+use super::connector::{ConnectionDetails, DatabaseType};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Fills the fields left empty in `details` from the places psql and mysql
+/// look for them: libpq/MySQL environment variables, `~/.pgpass` and the
+/// `[client]` section of `~/.my.cnf`. Explicitly given values always win.
+pub fn fill_missing(details: &mut ConnectionDetails) {
+    let env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+    apply_env(details, env);
+
+    match details.db_type {
+        DatabaseType::PostgreSQL => {
+            if is_missing(&details.password) {
+                let path = env("PGPASSFILE")
+                    .map(PathBuf::from)
+                    .or_else(|| dirs::home_dir().map(|home| home.join(".pgpass")));
+                if let Some(content) = path.and_then(|p| fs::read_to_string(p).ok()) {
+                    details.password = pgpass_lookup(&content, details);
+                }
+            }
+        }
+        DatabaseType::MySQL => {
+            let path = dirs::home_dir().map(|home| home.join(".my.cnf"));
+            if let Some(content) = path.and_then(|p| fs::read_to_string(p).ok()) {
+                apply_my_cnf(details, &parse_my_cnf(&content));
+            }
+        }
+        DatabaseType::SQLite => {}
+    }
+}
+
+fn is_missing(value: &Option<String>) -> bool {
+    value.as_deref().is_none_or(str::is_empty)
+}
+
+fn set_if_missing(field: &mut Option<String>, value: Option<String>) {
+    if is_missing(field) {
+        *field = value;
+    }
+}
+
+fn apply_env(details: &mut ConnectionDetails, env: impl Fn(&str) -> Option<String>) {
+    let (host, port, user, password, database) = match details.db_type {
+        DatabaseType::PostgreSQL => ("PGHOST", "PGPORT", "PGUSER", "PGPASSWORD", "PGDATABASE"),
+        DatabaseType::MySQL => (
+            "MYSQL_HOST",
+            "MYSQL_TCP_PORT",
+            "MYSQL_USER",
+            "MYSQL_PWD",
+            "MYSQL_DATABASE",
+        ),
+        DatabaseType::SQLite => return,
+    };
+
+    set_if_missing(&mut details.host, env(host));
+    set_if_missing(&mut details.username, env(user));
+    set_if_missing(&mut details.password, env(password));
+    set_if_missing(&mut details.database, env(database));
+    if details.port.is_none() {
+        details.port = env(port).and_then(|p| p.parse().ok());
+    }
+    if details.db_type == DatabaseType::MySQL {
+        set_if_missing(&mut details.host, env("MYSQL_UNIX_PORT"));
+    }
+}
+
+/// Splits a `.pgpass` line on unescaped colons, resolving `\:` and `\\`.
+fn split_pgpass_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    fields.last_mut().unwrap().push(next);
+                }
+            }
+            ':' => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+/// Returns the password of the first `.pgpass` entry matching the connection,
+/// using the same defaults and `*` wildcards as libpq.
+fn pgpass_lookup(content: &str, details: &ConnectionDetails) -> Option<String> {
+    // libpq matches unix-socket connections against the `localhost` entry.
+    let host = match details.host.as_deref() {
+        Some(h) if !h.is_empty() && !h.starts_with('/') => h,
+        _ => "localhost",
+    };
+    let port = details.port.unwrap_or(5432).to_string();
+    let user = details.username.as_deref().unwrap_or_default();
+    let database = details
+        .database
+        .as_deref()
+        .filter(|d| !d.is_empty())
+        .unwrap_or(user);
+
+    content
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(split_pgpass_line)
+        .filter(|fields| fields.len() == 5)
+        .find(|fields| {
+            [host, port.as_str(), database, user]
+                .iter()
+                .zip(fields.iter())
+                .all(|(value, pattern)| pattern == "*" || pattern == value)
+        })
+        .map(|mut fields| fields.remove(4))
+}
+
+/// Reads the `[client]` section of a MySQL option file.
+fn parse_my_cnf(content: &str) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    let mut in_client = false;
+
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_client = section.trim().eq_ignore_ascii_case("client");
+            continue;
+        }
+        if !in_client {
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').unwrap_or((line, ""));
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+            .unwrap_or(value);
+        // Option names treat `-` and `_` interchangeably.
+        values.insert(key.trim().replace('-', "_"), value.to_string());
+    }
+
+    values
+}
+
+fn apply_my_cnf(details: &mut ConnectionDetails, values: &HashMap<String, String>) {
+    let get = |key: &str| values.get(key).filter(|v| !v.is_empty()).cloned();
+
+    set_if_missing(&mut details.host, get("host").or_else(|| get("socket")));
+    set_if_missing(&mut details.username, get("user"));
+    set_if_missing(&mut details.password, get("password"));
+    set_if_missing(&mut details.database, get("database"));
+    if details.port.is_none() {
+        details.port = get("port").and_then(|p| p.parse().ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn postgres(host: &str, user: &str, database: Option<&str>) -> ConnectionDetails {
+        ConnectionDetails {
+            db_type: DatabaseType::PostgreSQL,
+            host: Some(host.to_string()),
+            port: None,
+            username: Some(user.to_string()),
+            password: None,
+            database: database.map(str::to_string),
+            file_path: None,
+            params: vec![],
+        }
+    }
+
+    #[test]
+    fn test_pgpass_wildcards_and_order() {
+        let content = "\
+# comment
+db.internal:5432:billing:app:billing-secret
+*:*:*:app:fallback
+localhost:5432:*:postgres:local\\:pass
+";
+        let billing = postgres("db.internal", "app", Some("billing"));
+        assert_eq!(
+            pgpass_lookup(content, &billing).as_deref(),
+            Some("billing-secret")
+        );
+
+        let other = postgres("db.internal", "app", Some("audit"));
+        assert_eq!(pgpass_lookup(content, &other).as_deref(), Some("fallback"));
+
+        let socket = postgres("/var/run/postgresql", "postgres", None);
+        assert_eq!(
+            pgpass_lookup(content, &socket).as_deref(),
+            Some("local:pass")
+        );
+
+        let unknown = postgres("db.internal", "nobody", None);
+        assert_eq!(pgpass_lookup(content, &unknown), None);
+    }
+
+    #[test]
+    fn test_env_only_fills_missing_fields() {
+        let mut details = postgres("", "explicit", None);
+        let env = |key: &str| match key {
+            "PGHOST" => Some("pg.example.com".to_string()),
+            "PGPORT" => Some("6432".to_string()),
+            "PGUSER" => Some("from_env".to_string()),
+            "PGDATABASE" => Some("app".to_string()),
+            _ => None,
+        };
+        apply_env(&mut details, env);
+
+        assert_eq!(details.host.as_deref(), Some("pg.example.com"));
+        assert_eq!(details.port, Some(6432));
+        assert_eq!(details.username.as_deref(), Some("explicit"));
+        assert_eq!(details.database.as_deref(), Some("app"));
+        assert_eq!(details.password, None);
+    }
+
+    #[test]
+    fn test_my_cnf_client_section() {
+        let content = "\
+[mysqld]
+port = 3307
+
+[client]
+user = root
+password = \"s3cr#t\"
+default-character-set=utf8mb4
+socket=/tmp/mysql.sock
+";
+        let values = parse_my_cnf(content);
+        let mut details = ConnectionDetails {
+            db_type: DatabaseType::MySQL,
+            host: None,
+            port: None,
+            username: None,
+            password: None,
+            database: Some("shop".to_string()),
+            file_path: None,
+            params: vec![],
+        };
+        apply_my_cnf(&mut details, &values);
+
+        assert_eq!(details.username.as_deref(), Some("root"));
+        assert_eq!(details.password.as_deref(), Some("s3cr#t"));
+        assert_eq!(details.host.as_deref(), Some("/tmp/mysql.sock"));
+        assert_eq!(details.port, None);
+        assert_eq!(details.database.as_deref(), Some("shop"));
+    }
+}
@@ -0,0 +1,319 @@
+use super::connector::DatabaseType;
+use super::fetch::{ObjectKind, SchemaObject, TableMetadata, mysql_text};
+use super::pool::DbPool;
+use crate::crud::browse::{qualified_name, quote_identifier};
+use color_eyre::eyre::{Result, eyre};
+use sqlx::{MySqlPool, PgPool, Row, SqlitePool};
+
+/// Resolves the relation the Postgres DDL queries below are about.
+const PG_RELATION: &str = r#"
+    WITH rel AS (
+        SELECT c.oid, c.relkind
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1 AND c.relname = $2
+    )
+"#;
+
+/// Reconstructs the DDL of a table: the `CREATE` statement with its columns
+/// and constraints, followed by its indexes and triggers.
+pub async fn table_ddl(pool: &DbPool, table: &TableMetadata) -> Result<String> {
+    match pool {
+        DbPool::Postgres(pg) => pg_table_ddl(pg, table).await,
+        DbPool::MySQL(mysql) => mysql_table_ddl(mysql, table).await,
+        DbPool::SQLite(sqlite) => sqlite_table_ddl(sqlite, table).await,
+    }
+}
+
+/// Returns the source of a function, procedure, sequence, type or extension.
+pub async fn object_ddl(pool: &DbPool, object: &SchemaObject) -> Result<String> {
+    let identity = &object.identity;
+    match (pool, object.kind) {
+        (_, ObjectKind::EnumType) => Ok(format!(
+            "CREATE TYPE {} AS ENUM ({});",
+            identity, object.detail
+        )),
+        (_, ObjectKind::CompositeType) => {
+            Ok(format!("CREATE TYPE {} AS ({});", identity, object.detail))
+        }
+        (_, ObjectKind::Extension) => Ok(format!(
+            "CREATE EXTENSION IF NOT EXISTS {} WITH SCHEMA {} VERSION '{}';",
+            quote_identifier(DatabaseType::PostgreSQL, &object.name),
+            quote_identifier(DatabaseType::PostgreSQL, &object.schema),
+            object.detail.replace('\'', "''")
+        )),
+        (DbPool::Postgres(pg), ObjectKind::Function | ObjectKind::Procedure) => {
+            Ok(
+                sqlx::query_scalar("SELECT pg_get_functiondef($1::regprocedure)")
+                    .bind(identity)
+                    .fetch_one(pg)
+                    .await?,
+            )
+        }
+        (DbPool::Postgres(pg), ObjectKind::Sequence) => Ok(sqlx::query_scalar(
+            r#"
+                SELECT format(
+                    'CREATE SEQUENCE %s AS %s INCREMENT BY %s MINVALUE %s MAXVALUE %s START WITH %s CACHE %s%s;',
+                    $1, format_type(seqtypid, NULL), seqincrement, seqmin, seqmax, seqstart, seqcache,
+                    CASE WHEN seqcycle THEN ' CYCLE' ELSE '' END
+                )
+                FROM pg_sequence
+                WHERE seqrelid = $1::regclass
+            "#,
+        )
+        .bind(identity)
+        .fetch_one(pg)
+        .await?),
+        (DbPool::MySQL(mysql), ObjectKind::Function | ObjectKind::Procedure) => {
+            let kind = if object.kind == ObjectKind::Function {
+                "FUNCTION"
+            } else {
+                "PROCEDURE"
+            };
+            // Column 2 is `Create Function` or `Create Procedure`.
+            let row = sqlx::query(&format!("SHOW CREATE {} {}", kind, identity))
+                .fetch_one(mysql)
+                .await?;
+            Ok(mysql_text(&row, 2))
+        }
+        _ => Err(eyre!("{} has no source to show", object.name)),
+    }
+}
+
+async fn pg_lines(pool: &PgPool, sql: &str, table: &TableMetadata) -> sqlx::Result<Vec<String>> {
+    let rows = sqlx::query(&format!("{PG_RELATION} {sql}"))
+        .bind(&table.schema)
+        .bind(&table.name)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(|r| r.get("line")).collect())
+}
+
+async fn pg_table_ddl(pool: &PgPool, table: &TableMetadata) -> Result<String> {
+    let name = qualified_name(DatabaseType::PostgreSQL, table);
+
+    let relkind = pg_lines(pool, "SELECT relkind::TEXT AS line FROM rel", table)
+        .await?
+        .pop()
+        .ok_or_else(|| eyre!("{}.{} no longer exists", table.schema, table.name))?;
+
+    if relkind == "v" || relkind == "m" {
+        let definition = pg_lines(
+            pool,
+            "SELECT pg_get_viewdef(rel.oid, true) AS line FROM rel",
+            table,
+        )
+        .await?
+        .join("");
+        let kind = if relkind == "m" {
+            "MATERIALIZED VIEW"
+        } else {
+            "OR REPLACE VIEW"
+        };
+        return Ok(format!(
+            "CREATE {} {} AS\n{}",
+            kind,
+            name,
+            definition.trim_end()
+        ));
+    }
+
+    let (columns, partition_key, constraints, indexes, triggers, rules) = tokio::try_join!(
+        pg_lines(
+            pool,
+            r#"
+                SELECT format('%I %s', a.attname, format_type(a.atttypid, a.atttypmod))
+                    || CASE
+                        WHEN a.attgenerated = 's'
+                            THEN ' GENERATED ALWAYS AS (' || pg_get_expr(d.adbin, d.adrelid) || ') STORED'
+                        WHEN d.adbin IS NOT NULL THEN ' DEFAULT ' || pg_get_expr(d.adbin, d.adrelid)
+                        ELSE ''
+                    END
+                    || CASE a.attidentity
+                        WHEN 'a' THEN ' GENERATED ALWAYS AS IDENTITY'
+                        WHEN 'd' THEN ' GENERATED BY DEFAULT AS IDENTITY'
+                        ELSE ''
+                    END
+                    || CASE WHEN a.attnotnull THEN ' NOT NULL' ELSE '' END AS line
+                FROM rel
+                JOIN pg_attribute a ON a.attrelid = rel.oid
+                LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+                WHERE a.attnum > 0 AND NOT a.attisdropped
+                ORDER BY a.attnum
+            "#,
+            table,
+        ),
+        pg_lines(
+            pool,
+            r#"
+                SELECT pg_get_partkeydef(rel.oid) AS line
+                FROM rel
+                WHERE pg_get_partkeydef(rel.oid) IS NOT NULL
+            "#,
+            table,
+        ),
+        pg_lines(
+            pool,
+            r#"
+                SELECT format('CONSTRAINT %I %s', k.conname, pg_get_constraintdef(k.oid)) AS line
+                FROM rel
+                JOIN pg_constraint k ON k.conrelid = rel.oid
+                WHERE k.contype IN ('p', 'u', 'f', 'c', 'x')
+                ORDER BY k.contype = 'p' DESC, k.conname
+            "#,
+            table,
+        ),
+        pg_lines(
+            pool,
+            r#"
+                SELECT pg_get_indexdef(x.indexrelid) || ';' AS line
+                FROM rel
+                JOIN pg_index x ON x.indrelid = rel.oid
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM pg_constraint k
+                    WHERE k.conindid = x.indexrelid AND k.contype IN ('p', 'u', 'x')
+                )
+                ORDER BY x.indexrelid::regclass::TEXT
+            "#,
+            table,
+        ),
+        pg_lines(
+            pool,
+            r#"
+                SELECT pg_get_triggerdef(t.oid, true) || ';' AS line
+                FROM rel
+                JOIN pg_trigger t ON t.tgrelid = rel.oid
+                WHERE NOT t.tgisinternal
+                ORDER BY t.tgname
+            "#,
+            table,
+        ),
+        pg_lines(
+            pool,
+            r#"
+                SELECT pg_get_ruledef(r.oid, true) AS line
+                FROM rel
+                JOIN pg_rewrite r ON r.ev_class = rel.oid
+                WHERE r.rulename <> '_RETURN'
+                ORDER BY r.rulename
+            "#,
+            table,
+        ),
+    )?;
+
+    let body: Vec<String> = columns.into_iter().chain(constraints).collect();
+    let partition_by: String = partition_key
+        .iter()
+        .map(|key| format!(" PARTITION BY {}", key))
+        .collect();
+    let mut statements = vec![format!(
+        "CREATE TABLE {} (\n    {}\n){};",
+        name,
+        body.join(",\n    "),
+        partition_by
+    )];
+    statements.extend(indexes);
+    statements.extend(triggers);
+    statements.extend(rules);
+    Ok(statements.join("\n\n"))
+}
+
+async fn mysql_table_ddl(pool: &MySqlPool, table: &TableMetadata) -> Result<String> {
+    let name = qualified_name(DatabaseType::MySQL, table);
+
+    // Column 1 is `Create Table`, or `Create View` for views.
+    let create = sqlx::query(&format!("SHOW CREATE TABLE {}", name))
+        .fetch_one(pool)
+        .await?;
+    let mut statements = vec![format!("{};", mysql_text(&create, 1))];
+
+    let triggers = sqlx::query(
+        r#"
+            SELECT CAST(TRIGGER_NAME AS CHAR) AS name
+            FROM information_schema.TRIGGERS
+            WHERE EVENT_OBJECT_SCHEMA = ? AND EVENT_OBJECT_TABLE = ?
+            ORDER BY ACTION_ORDER
+        "#,
+    )
+    .bind(&table.schema)
+    .bind(&table.name)
+    .fetch_all(pool)
+    .await?;
+    // Trigger bodies hold `;`, so they end with a delimiter of their own.
+    if !triggers.is_empty() {
+        statements.push("DELIMITER //".to_string());
+    }
+    for trigger in &triggers {
+        let trigger_name: String = trigger.get("name");
+        let qualified = format!(
+            "`{}`.`{}`",
+            table.schema.replace('`', "``"),
+            trigger_name.replace('`', "``")
+        );
+        // Column 2 is `SQL Original Statement`.
+        let row = sqlx::query(&format!("SHOW CREATE TRIGGER {}", qualified))
+            .fetch_one(pool)
+            .await?;
+        statements.push(format!("{} //", mysql_text(&row, 2)));
+    }
+    if !triggers.is_empty() {
+        statements.push("DELIMITER ;".to_string());
+    }
+
+    Ok(statements.join("\n\n"))
+}
+
+async fn sqlite_table_ddl(pool: &SqlitePool, table: &TableMetadata) -> Result<String> {
+    // Indexes created for PRIMARY KEY and UNIQUE constraints have no `sql`.
+    let rows = sqlx::query(
+        r#"
+            SELECT sql FROM sqlite_master
+            WHERE tbl_name = ? AND sql IS NOT NULL
+            ORDER BY CASE type WHEN 'table' THEN 0 WHEN 'view' THEN 0 WHEN 'index' THEN 1 ELSE 2 END,
+                name
+        "#,
+    )
+    .bind(&table.name)
+    .fetch_all(pool)
+    .await?;
+
+    if rows.is_empty() {
+        return Err(eyre!("{} no longer exists", table.name));
+    }
+
+    let statements: Vec<String> = rows
+        .iter()
+        .map(|row| format!("{};", row.get::<String, _>("sql")))
+        .collect();
+    Ok(statements.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::pool::sqlite_test_pool;
+
+    #[tokio::test]
+    async fn test_sqlite_ddl_lists_table_then_indexes_and_triggers() {
+        let pool = sqlite_test_pool(
+            "CREATE TABLE posts (id INTEGER PRIMARY KEY, title TEXT UNIQUE);
+             CREATE TRIGGER posts_trg AFTER INSERT ON posts BEGIN SELECT 1; END;
+             CREATE INDEX posts_title ON posts (title);",
+        )
+        .await;
+
+        let table = TableMetadata {
+            schema: "main".to_string(),
+            name: "posts".to_string(),
+            ..TableMetadata::default()
+        };
+        let ddl = table_ddl(&pool, &table).await.unwrap();
+
+        assert_eq!(
+            ddl,
+            "CREATE TABLE posts (id INTEGER PRIMARY KEY, title TEXT UNIQUE);\n\n\
+             CREATE INDEX posts_title ON posts (title);\n\n\
+             CREATE TRIGGER posts_trg AFTER INSERT ON posts BEGIN SELECT 1; END;"
+        );
+    }
+}
@@ -1,51 +1,177 @@
-use color_eyre::eyre::Result;
-use std::process::Command;
-
-#[derive(Debug)]
-pub struct DatabaseChecker {
-    pub name: &'static str,
-    pub command: &'static str,
-    pub args: &'static [&'static str],
-}
-
-pub fn get_installed_databases() -> Result<Vec<String>> {
-    let db_tools = [
-        DatabaseChecker {
-            name: "PostgreSQL",
-            command: "pg_isready",
-            args: &[],
-        },
-        DatabaseChecker {
-            name: "MySQL",
-            command: "mysql",
-            args: &["--version"],
-        },
-        DatabaseChecker {
-            name: "SQLite",
-            command: "sqlite3",
-            args: &["--version"],
-        },
-    ];
-
-    let mut found = Vec::new();
-
-    for tool in db_tools.iter() {
-        match Command::new(tool.command).args(tool.args).output() {
-            Ok(output) if output.status.success() => {
-                found.push(tool.name.to_string());
-            }
-            Ok(output) => {
-                eprintln!(
-                    "⚠️ {} found but returned err: {}",
-                    tool.name,
-                    String::from_utf8_lossy(&output.stderr)
-                )
-            }
-            Err(e) => {
-                eprintln!("❗ Failed to run {}: {}", tool.name, e)
-            }
-        }
-    }
-
-    Ok(found)
+use super::connector::DatabaseType;
+use futures::future::join_all;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+/// How long a single TCP or socket probe may take before it counts as closed.
+const PROBE_TIMEOUT: Duration = Duration::from_millis(300);
+
+const PG_SOCKET_DIRS: &[&str] = &["/var/run/postgresql", "/tmp"];
+const MYSQL_SOCKETS: &[&str] = &[
+    "/var/run/mysqld/mysqld.sock",
+    "/run/mysqld/mysqld.sock",
+    "/tmp/mysql.sock",
+];
+
+/// An engine offered in the startup picker, with the address it was found at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedDatabase {
+    pub db_type: DatabaseType,
+    /// Whether a local server answered the probe. SQLite is always available.
+    pub available: bool,
+    /// Host or unix-socket path the connector can prefill.
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub reason: String,
+}
+
+impl std::fmt::Display for DetectedDatabase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let marker = if self.available { "✅" } else { "⚪" };
+        write!(f, "{} {} — {}", marker, self.db_type, self.reason)
+    }
+}
+
+/// Probes the default ports and sockets of each supported engine.
+///
+/// Engines without a reachable local server are still returned, after the
+/// reachable ones, so remote servers can be entered by hand.
+pub async fn get_installed_databases() -> Vec<DetectedDatabase> {
+    let (postgres, mysql) = tokio::join!(detect_postgres(), detect_mysql());
+
+    let mut found = vec![postgres, mysql, detect_sqlite()];
+    found.sort_by_key(|db| !db.available);
+    found
+}
+
+async fn detect_postgres() -> DetectedDatabase {
+    let port = 5432;
+    let sockets = PG_SOCKET_DIRS
+        .iter()
+        .map(|dir| (dir.to_string(), format!("{dir}/.s.PGSQL.{port}")));
+    detect_server(DatabaseType::PostgreSQL, port, sockets).await
+}
+
+async fn detect_mysql() -> DetectedDatabase {
+    let port = 3306;
+    let sockets = MYSQL_SOCKETS
+        .iter()
+        .map(|path| (path.to_string(), path.to_string()));
+    detect_server(DatabaseType::MySQL, port, sockets).await
+}
+
+fn detect_sqlite() -> DetectedDatabase {
+    DetectedDatabase {
+        db_type: DatabaseType::SQLite,
+        available: true,
+        host: None,
+        port: None,
+        reason: "embedded, no server needed".to_string(),
+    }
+}
+
+/// Tries `127.0.0.1:<port>` first, then each `(host, socket path)` pair.
+async fn detect_server(
+    db_type: DatabaseType,
+    port: u16,
+    sockets: impl Iterator<Item = (String, String)>,
+) -> DetectedDatabase {
+    let tcp_address = format!("127.0.0.1:{port}");
+    let sockets: Vec<(String, String)> = sockets.collect();
+
+    let (tcp_open, socket_results) = tokio::join!(
+        probe_tcp(&tcp_address),
+        join_all(sockets.iter().map(|(_, path)| probe_socket(path)))
+    );
+
+    if tcp_open {
+        return DetectedDatabase {
+            db_type,
+            available: true,
+            host: Some("localhost".to_string()),
+            port: Some(port),
+            reason: format!("listening on {tcp_address}"),
+        };
+    }
+
+    if let Some(((host, path), _)) = sockets
+        .into_iter()
+        .zip(socket_results)
+        .find(|(_, open)| *open)
+    {
+        return DetectedDatabase {
+            db_type,
+            available: true,
+            host: Some(host),
+            port: Some(port),
+            reason: format!("listening on {path}"),
+        };
+    }
+
+    DetectedDatabase {
+        db_type,
+        available: false,
+        host: None,
+        port: None,
+        reason: format!("no local server on port {port}, enter a remote host"),
+    }
+}
+
+async fn probe_tcp(address: &str) -> bool {
+    matches!(
+        timeout(PROBE_TIMEOUT, TcpStream::connect(address)).await,
+        Ok(Ok(_))
+    )
+}
+
+#[cfg(unix)]
+async fn probe_socket(path: &str) -> bool {
+    use std::path::Path;
+
+    if !Path::new(path).exists() {
+        return false;
+    }
+    matches!(
+        timeout(PROBE_TIMEOUT, tokio::net::UnixStream::connect(path)).await,
+        Ok(Ok(_))
+    )
+}
+
+#[cfg(not(unix))]
+async fn probe_socket(_path: &str) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_detect_server_finds_open_port() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let detected = detect_server(DatabaseType::PostgreSQL, port, std::iter::empty()).await;
+        assert!(detected.available);
+        assert_eq!(detected.host.as_deref(), Some("localhost"));
+        assert_eq!(detected.port, Some(port));
+    }
+
+    #[tokio::test]
+    async fn test_detect_server_reports_closed_port() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let detected = detect_server(DatabaseType::MySQL, port, std::iter::empty()).await;
+        assert!(!detected.available);
+        assert_eq!(detected.host, None);
+    }
+
+    #[test]
+    fn test_sqlite_is_always_available() {
+        assert!(detect_sqlite().available);
+    }
 }
@@ -1,18 +1,52 @@
 use crate::layout::data_table::DynamicData;
+use crate::utils::fuzzy::{fuzzy_match, highlight_match};
 
 use super::pool::DbPool;
 use color_eyre::eyre::Result;
-use futures::future::try_join_all;
-use ratatui::text::Text;
+use ratatui::text::{Line, Text};
 use sqlx::{MySqlPool, PgPool, Row, SqlitePool};
+use std::collections::HashMap;
 
 use tui_tree_widget::TreeItem;
 
+/// A column as shown in the sidebar, e.g. `id  bigint  PK  NOT NULL`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnMetadata {
+    pub name: String,
+    pub data_type: String,
+    pub nullable: bool,
+    pub default: Option<String>,
+    pub primary_key: bool,
+    /// Referenced `table(column)` when the column is part of a foreign key.
+    pub references: Option<String>,
+}
+
+impl ColumnMetadata {
+    pub fn label(&self) -> String {
+        let mut parts = vec![self.name.clone(), self.data_type.clone()];
+        if self.primary_key {
+            parts.push("PK".to_string());
+        }
+        if !self.nullable {
+            parts.push("NOT NULL".to_string());
+        }
+        if let Some(default) = &self.default {
+            parts.push(format!("DEFAULT {}", default));
+        }
+        if let Some(references) = &self.references {
+            parts.push(format!("→ {}", references));
+        }
+        parts.join("  ")
+    }
+}
+
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct TableMetadata {
+    /// Postgres schema, MySQL database or SQLite schema (`main`) holding the table.
+    pub schema: String,
     pub name: String,
-    pub columns: Vec<String>,
+    pub columns: Vec<ColumnMetadata>,
     pub constraints: Vec<String>,
     pub indexes: Vec<String>,
     pub rls_policies: Vec<String>,
@@ -22,6 +56,75 @@ pub struct TableMetadata {
     pub estimated_size: String,
     pub table_type: String,
     pub table_data: Option<DynamicData>,
+    /// Whether the per-table details above have been fetched yet.
+    pub details_loaded: bool,
+    /// Column and index names fetched for the sidebar filter, so that a
+    /// table can match before its details are loaded.
+    pub search_names: Option<Vec<String>>,
+}
+
+/// Kinds of schema objects listed in the sidebar next to the tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    Function,
+    Procedure,
+    Sequence,
+    EnumType,
+    CompositeType,
+    Extension,
+}
+
+impl ObjectKind {
+    /// Parses the kind names the catalog queries below return.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "function" => Some(ObjectKind::Function),
+            "procedure" => Some(ObjectKind::Procedure),
+            "sequence" => Some(ObjectKind::Sequence),
+            "enum" => Some(ObjectKind::EnumType),
+            "composite" => Some(ObjectKind::CompositeType),
+            "extension" => Some(ObjectKind::Extension),
+            _ => None,
+        }
+    }
+
+    /// Identifier and label of the sidebar group holding objects of this kind.
+    pub fn group(&self) -> (&'static str, &'static str) {
+        match self {
+            ObjectKind::Function => ("functions", "Functions"),
+            ObjectKind::Procedure => ("procedures", "Procedures"),
+            ObjectKind::Sequence => ("sequences", "Sequences"),
+            ObjectKind::EnumType | ObjectKind::CompositeType => ("types", "Types"),
+            ObjectKind::Extension => (EXTENSIONS_GROUP, "Extensions"),
+        }
+    }
+}
+
+/// A function, procedure, sequence, custom type or extension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaObject {
+    pub schema: String,
+    pub name: String,
+    pub kind: ObjectKind,
+    /// Signature of a routine, data type of a sequence, values of an enum,
+    /// fields of a composite type or version of an extension.
+    pub detail: String,
+    /// Unique, engine-specific name used to fetch the source, e.g.
+    /// `public.add(integer,integer)` for an overloaded Postgres function.
+    pub identity: String,
+}
+
+impl SchemaObject {
+    pub fn label(&self) -> String {
+        match self.kind {
+            ObjectKind::Function | ObjectKind::Procedure => format!("{}{}", self.name, self.detail),
+            ObjectKind::EnumType => format!("{}  enum ({})", self.name, self.detail),
+            ObjectKind::CompositeType => format!("{}  ({})", self.name, self.detail),
+            ObjectKind::Sequence | ObjectKind::Extension => {
+                format!("{}  {}", self.name, self.detail)
+            }
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -42,15 +145,70 @@ impl TableMetadataUtils for Vec<TableMetadata> {
 
 #[async_trait::async_trait]
 pub trait MetadataFetcher: Send + Sync {
-    async fn fetch_metadata(&self) -> Result<Vec<TableMetadata>>;
+    /// Lists the tables with their cheap statistics. Per-table details are
+    /// left empty until `fetch_table_details` is called for the table.
+    async fn fetch_tables(&self) -> Result<Vec<TableMetadata>>;
+
+    /// Loads columns, constraints, indexes, policies, rules and triggers of one table.
+    async fn fetch_table_details(&self, table: &TableMetadata) -> Result<TableMetadata>;
+
+    /// Loads the details of several tables at once. One table after the
+    /// other unless the database can do better.
+    async fn fetch_tables_details(&self, tables: &[TableMetadata]) -> Result<Vec<TableMetadata>> {
+        let mut loaded = Vec::with_capacity(tables.len());
+        for table in tables {
+            loaded.push(self.fetch_table_details(table).await?);
+        }
+        Ok(loaded)
+    }
+
+    /// Lists functions, procedures, sequences, custom types and extensions.
+    async fn fetch_objects(&self) -> Result<Vec<SchemaObject>> {
+        Ok(Vec::new())
+    }
 }
 
+/// Builds schema objects from rows with `schema_name`, `name`, `kind`,
+/// `detail` and `identity` columns, skipping kinds this version does not know.
+fn objects_from_rows<R>(rows: Vec<R>) -> Vec<SchemaObject>
+where
+    R: Row,
+    for<'r> &'r str: sqlx::ColumnIndex<R>,
+    for<'r> String: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    for<'r> Option<String>: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+{
+    rows.into_iter()
+        .filter_map(|row| {
+            let kind = ObjectKind::from_name(&row.get::<String, _>("kind"))?;
+            Some(SchemaObject {
+                schema: row.get("schema_name"),
+                name: row.get("name"),
+                kind,
+                detail: row.get::<Option<String>, _>("detail").unwrap_or_default(),
+                identity: row.get("identity"),
+            })
+        })
+        .collect()
+}
+
+/// Restricts a query joined to `pg_namespace n` to user-visible schemas.
+const PG_USER_SCHEMAS: &str = r#"
+    n.nspname NOT IN ('pg_catalog', 'information_schema')
+    AND n.nspname NOT LIKE 'pg\_toast%'
+    AND n.nspname NOT LIKE 'pg\_temp\_%'
+"#;
+
+/// Restricts a query joined to `pg_class c` to tables, views, materialized
+/// views and foreign tables.
+const PG_TABLE_KINDS: &str = "c.relkind IN ('r', 'p', 'v', 'm', 'f')";
+
 #[async_trait::async_trait]
 impl MetadataFetcher for PgPool {
-    async fn fetch_metadata(&self) -> Result<Vec<TableMetadata>> {
-        let rows = sqlx::query(
+    async fn fetch_tables(&self) -> Result<Vec<TableMetadata>> {
+        let tables_sql = format!(
             r#"
                 SELECT 
+                    n.nspname AS schema_name,
                     c.relname AS table_name,
                     CASE 
                         WHEN c.reltuples < 0 THEN 0
@@ -59,6 +217,7 @@ impl MetadataFetcher for PgPool {
                     pg_size_pretty(pg_total_relation_size(c.oid)) AS total_size,
                     CASE c.relkind 
                         WHEN 'r' THEN 'table'
+                        WHEN 'p' THEN 'partitioned table'
                         WHEN 'v' THEN 'view'
                         WHEN 'm' THEN 'materialized view'
                         WHEN 'f' THEN 'foreign table'
@@ -66,261 +225,1340 @@ impl MetadataFetcher for PgPool {
                     END AS table_type
                 FROM pg_class c
                 JOIN pg_namespace n ON n.oid = c.relnamespace
-                WHERE n.nspname = 'public' AND c.relkind IN ('r', 'v', 'm', 'f')
-                ORDER BY c.relname;
+                WHERE {PG_TABLE_KINDS} AND {PG_USER_SCHEMAS}
+                ORDER BY n.nspname, c.relname
+            "#
+        );
+
+        let rows = sqlx::query(&tables_sql).fetch_all(self).await?;
+        let metadata = rows
+            .into_iter()
+            .map(|row| TableMetadata {
+                schema: row.get("schema_name"),
+                name: row.get("table_name"),
+                row_count: row.get("row_estimate"),
+                estimated_size: row.get("total_size"),
+                table_type: row.get("table_type"),
+                ..TableMetadata::default()
+            })
+            .collect();
+
+        Ok(metadata)
+    }
+
+    async fn fetch_table_details(&self, table: &TableMetadata) -> Result<TableMetadata> {
+        let mut loaded = self
+            .fetch_tables_details(std::slice::from_ref(table))
+            .await?;
+        Ok(loaded.remove(0))
+    }
+
+    /// Loads the whole batch in a fixed number of queries, whatever the
+    /// number of tables, and stitches the results together by table.
+    async fn fetch_tables_details(&self, tables: &[TableMetadata]) -> Result<Vec<TableMetadata>> {
+        let constraints_sql = pg_children_sql(
+            "k.conname",
+            "pg_constraint k",
+            "k.conrelid",
+            "k.contype <> 'c'",
+            "k.conname",
+        );
+        let indexes_sql = pg_children_sql(
+            "i.relname",
+            "pg_index x JOIN pg_class i ON i.oid = x.indexrelid",
+            "x.indrelid",
+            "TRUE",
+            "i.relname",
+        );
+        let policies_sql = pg_children_sql(
+            "p.polname",
+            "pg_policy p",
+            "p.polrelid",
+            "TRUE",
+            "p.polname",
+        );
+        let rules_sql = pg_children_sql(
+            "r.rulename",
+            "pg_rewrite r",
+            "r.ev_class",
+            "r.rulename <> '_RETURN'",
+            "r.rulename",
+        );
+        let triggers_sql = pg_children_sql(
+            "t.tgname",
+            "pg_trigger t",
+            "t.tgrelid",
+            "NOT t.tgisinternal",
+            "t.tgname",
+        );
+
+        let (mut columns, mut constraints, mut indexes, mut policies, mut rules, mut triggers) = tokio::try_join!(
+            pg_columns_by_table(self, tables),
+            pg_names_by_table(self, &constraints_sql, tables),
+            pg_names_by_table(self, &indexes_sql, tables),
+            pg_names_by_table(self, &policies_sql, tables),
+            pg_names_by_table(self, &rules_sql, tables),
+            pg_names_by_table(self, &triggers_sql, tables),
+        )?;
+
+        Ok(tables
+            .iter()
+            .map(|table| {
+                let key = (table.schema.clone(), table.name.clone());
+                TableMetadata {
+                    columns: columns.remove(&key).unwrap_or_default(),
+                    constraints: constraints.remove(&key).unwrap_or_default(),
+                    indexes: indexes.remove(&key).unwrap_or_default(),
+                    rls_policies: policies.remove(&key).unwrap_or_default(),
+                    rules: rules.remove(&key).unwrap_or_default(),
+                    triggers: triggers.remove(&key).unwrap_or_default(),
+                    details_loaded: true,
+                    ..table.clone()
+                }
+            })
+            .collect())
+    }
+
+    async fn fetch_objects(&self) -> Result<Vec<SchemaObject>> {
+        pg_objects(self).await
+    }
+}
+
+/// Excludes objects created by an extension, which are listed under it instead.
+const PG_NOT_EXTENSION_MEMBER: &str = r#"
+    NOT EXISTS (
+        SELECT 1 FROM pg_depend dep
+        WHERE dep.objid = obj.oid AND dep.deptype = 'e'
+    )
+"#;
+
+async fn pg_objects(pool: &PgPool) -> Result<Vec<SchemaObject>> {
+    let routines_sql = format!(
+        r#"
+            SELECT
+                n.nspname AS schema_name,
+                obj.proname::TEXT AS name,
+                CASE obj.prokind WHEN 'p' THEN 'procedure' ELSE 'function' END AS kind,
+                '(' || pg_get_function_arguments(obj.oid) || ')'
+                    || CASE WHEN obj.prokind = 'p' THEN ''
+                        ELSE ' → ' || pg_get_function_result(obj.oid) END AS detail,
+                obj.oid::regprocedure::TEXT AS identity
+            FROM pg_proc obj
+            JOIN pg_namespace n ON n.oid = obj.pronamespace
+            WHERE obj.prokind IN ('f', 'p') AND {PG_USER_SCHEMAS} AND {PG_NOT_EXTENSION_MEMBER}
+            ORDER BY n.nspname, obj.proname, identity
+        "#
+    );
+    let sequences_sql = format!(
+        r#"
+            SELECT
+                n.nspname AS schema_name,
+                obj.relname::TEXT AS name,
+                'sequence' AS kind,
+                format_type(s.seqtypid, NULL) AS detail,
+                format('%I.%I', n.nspname, obj.relname) AS identity
+            FROM pg_class obj
+            JOIN pg_namespace n ON n.oid = obj.relnamespace
+            JOIN pg_sequence s ON s.seqrelid = obj.oid
+            WHERE obj.relkind = 'S' AND {PG_USER_SCHEMAS} AND {PG_NOT_EXTENSION_MEMBER}
+            ORDER BY n.nspname, obj.relname
+        "#
+    );
+    let types_sql = format!(
+        r#"
+            SELECT
+                n.nspname AS schema_name,
+                obj.typname::TEXT AS name,
+                CASE obj.typtype WHEN 'e' THEN 'enum' ELSE 'composite' END AS kind,
+                CASE obj.typtype
+                    WHEN 'e' THEN (
+                        SELECT string_agg(quote_literal(e.enumlabel), ', ' ORDER BY e.enumsortorder)
+                        FROM pg_enum e WHERE e.enumtypid = obj.oid
+                    )
+                    ELSE (
+                        SELECT string_agg(format('%I %s', a.attname, format_type(a.atttypid, a.atttypmod)),
+                            ', ' ORDER BY a.attnum)
+                        FROM pg_attribute a
+                        WHERE a.attrelid = obj.typrelid AND a.attnum > 0 AND NOT a.attisdropped
+                    )
+                END AS detail,
+                format('%I.%I', n.nspname, obj.typname) AS identity
+            FROM pg_type obj
+            JOIN pg_namespace n ON n.oid = obj.typnamespace
+            LEFT JOIN pg_class rel ON rel.oid = obj.typrelid
+            WHERE (obj.typtype = 'e' OR (obj.typtype = 'c' AND rel.relkind = 'c'))
+                AND {PG_USER_SCHEMAS} AND {PG_NOT_EXTENSION_MEMBER}
+            ORDER BY n.nspname, obj.typname
+        "#
+    );
+    let extensions_sql = r#"
+        SELECT
+            n.nspname AS schema_name,
+            e.extname::TEXT AS name,
+            'extension' AS kind,
+            e.extversion AS detail,
+            e.extname::TEXT AS identity
+        FROM pg_extension e
+        JOIN pg_namespace n ON n.oid = e.extnamespace
+        ORDER BY e.extname
+    "#;
+
+    let (routines, sequences, types, extensions) = tokio::try_join!(
+        sqlx::query(&routines_sql).fetch_all(pool),
+        sqlx::query(&sequences_sql).fetch_all(pool),
+        sqlx::query(&types_sql).fetch_all(pool),
+        sqlx::query(extensions_sql).fetch_all(pool),
+    )?;
+
+    Ok([routines, sequences, types, extensions]
+        .into_iter()
+        .flat_map(objects_from_rows)
+        .collect())
+}
+
+/// Catalog rows grouped by `(schema, table)`.
+pub type ByTable<T> = HashMap<(String, String), Vec<T>>;
+
+/// Restricts a query joined to `pg_class c` and `pg_namespace n` to the
+/// tables whose schemas are bound to `$1` and names to `$2`.
+const PG_TABLES_IN_BATCH: &str = r#"
+    JOIN unnest($1::TEXT[], $2::TEXT[]) AS batch(schema_name, table_name)
+        ON batch.schema_name = n.nspname AND batch.table_name = c.relname
+"#;
+
+/// Builds a query returning `(schema_name, table_name, name)` for every
+/// catalog object of one kind that belongs to a table of the batch,
+/// ordered per table.
+fn pg_children_sql(
+    name: &str,
+    from: &str,
+    join_column: &str,
+    filter: &str,
+    order_by: &str,
+) -> String {
+    format!(
+        r#"
+            SELECT n.nspname AS schema_name, c.relname AS table_name, {name}::TEXT AS name
+            FROM {from}
+            JOIN pg_class c ON c.oid = {join_column}
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            {PG_TABLES_IN_BATCH}
+            WHERE {filter}
+            ORDER BY n.nspname, c.relname, {order_by}
+        "#
+    )
+}
+
+/// Binds the schemas and names of `tables` for `PG_TABLES_IN_BATCH`.
+fn pg_batch<'q>(
+    sql: &'q str,
+    tables: &[TableMetadata],
+) -> sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments> {
+    let (schemas, names): (Vec<String>, Vec<String>) = tables
+        .iter()
+        .map(|t| (t.schema.clone(), t.name.clone()))
+        .unzip();
+    sqlx::query(sql).bind(schemas).bind(names)
+}
+
+async fn pg_columns_by_table(
+    pool: &PgPool,
+    tables: &[TableMetadata],
+) -> sqlx::Result<ByTable<ColumnMetadata>> {
+    let sql = format!(
+        r#"
+            SELECT
+                n.nspname AS schema_name,
+                c.relname AS table_name,
+                a.attname::TEXT AS name,
+                format_type(a.atttypid, a.atttypmod) AS data_type,
+                NOT a.attnotnull AS nullable,
+                pg_get_expr(d.adbin, d.adrelid) AS default_value,
+                EXISTS (
+                    SELECT 1 FROM pg_constraint p
+                    WHERE p.conrelid = c.oid AND p.contype = 'p' AND a.attnum = ANY (p.conkey)
+                ) AS primary_key,
+                (
+                    SELECT format('%I.%I(%I)', fn.nspname, fc.relname, fa.attname)
+                    FROM pg_constraint f
+                    JOIN pg_class fc ON fc.oid = f.confrelid
+                    JOIN pg_namespace fn ON fn.oid = fc.relnamespace
+                    JOIN pg_attribute fa ON fa.attrelid = f.confrelid
+                        AND fa.attnum = f.confkey[array_position(f.conkey, a.attnum)]
+                    WHERE f.conrelid = c.oid AND f.contype = 'f' AND a.attnum = ANY (f.conkey)
+                    LIMIT 1
+                ) AS references_to
+            FROM pg_attribute a
+            JOIN pg_class c ON c.oid = a.attrelid
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            {PG_TABLES_IN_BATCH}
+            LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+            WHERE a.attnum > 0 AND NOT a.attisdropped
+            ORDER BY n.nspname, c.relname, a.attnum
+        "#
+    );
+    let rows = pg_batch(&sql, tables).fetch_all(pool).await?;
+
+    let mut columns: ByTable<ColumnMetadata> = HashMap::new();
+    for r in rows {
+        columns
+            .entry((r.get("schema_name"), r.get("table_name")))
+            .or_default()
+            .push(ColumnMetadata {
+                name: r.get("name"),
+                data_type: r.get("data_type"),
+                nullable: r.get("nullable"),
+                default: r.get("default_value"),
+                primary_key: r.get("primary_key"),
+                references: r.get("references_to"),
+            });
+    }
+    Ok(columns)
+}
+
+async fn pg_names_by_table(
+    pool: &PgPool,
+    sql: &str,
+    tables: &[TableMetadata],
+) -> sqlx::Result<ByTable<String>> {
+    let rows = pg_batch(sql, tables).fetch_all(pool).await?;
+
+    let mut names: ByTable<String> = HashMap::new();
+    for row in rows {
+        names
+            .entry((row.get("schema_name"), row.get("table_name")))
+            .or_default()
+            .push(row.get("name"));
+    }
+    Ok(names)
+}
+
+#[async_trait::async_trait]
+impl MetadataFetcher for MySqlPool {
+    async fn fetch_tables(&self) -> Result<Vec<TableMetadata>> {
+        let rows = sqlx::query(
+            r#"
+                SELECT
+                    CAST(TABLE_SCHEMA AS CHAR) AS table_schema,
+                    CAST(TABLE_NAME AS CHAR) AS table_name,
+                    CAST(COALESCE(TABLE_ROWS, 0) AS SIGNED) AS table_rows,
+                    CAST(COALESCE(DATA_LENGTH, 0) + COALESCE(INDEX_LENGTH, 0) AS SIGNED) AS total_size,
+                    CAST(TABLE_TYPE AS CHAR) AS table_type
+                FROM information_schema.TABLES
+                WHERE TABLE_SCHEMA NOT IN ('mysql', 'information_schema', 'performance_schema', 'sys')
+                ORDER BY TABLE_SCHEMA, TABLE_NAME
             "#,
         )
         .fetch_all(self)
         .await?;
 
-        let table_futures = rows.into_iter().map(|row| {
-            let pool = self.clone();
-            async move {
-                let table_name: String = row.get("table_name");
-                let row_count: i64 = row.get("row_estimate");
-                let estimated_size: String = row.get("total_size");
-                let table_type: String = row.get("table_type");
-
-                let columns = get_pg_columns(&pool, &table_name).await?;
-                let constraints = get_pg_constraints(&pool, &table_name).await?;
-                let indexes = get_pg_indexes(&pool, &table_name).await?;
-                let rls_policies = get_pg_rls_policies(&pool, &table_name).await?;
-                let rules = get_pg_rules(&pool, &table_name).await?;
-                let triggers = get_pg_triggers(&pool, &table_name).await?;
-
-                Ok::<_, sqlx::Error>(TableMetadata {
-                    name: table_name,
-                    columns,
-                    constraints,
-                    indexes,
-                    rls_policies,
-                    rules,
-                    triggers,
-                    row_count,
-                    estimated_size,
-                    table_type,
-                    table_data: None,
-                })
+        let tables = rows
+            .into_iter()
+            .map(|row| {
+                let total_size: i64 = row.try_get("total_size").unwrap_or(0);
+                TableMetadata {
+                    schema: row.get("table_schema"),
+                    name: row.get("table_name"),
+                    row_count: row.try_get("table_rows").unwrap_or(0),
+                    estimated_size: human_size(total_size),
+                    table_type: match row.get::<String, _>("table_type").as_str() {
+                        "BASE TABLE" => "table".to_string(),
+                        "SYSTEM VIEW" => "system view".to_string(),
+                        other => other.to_lowercase(),
+                    },
+                    ..TableMetadata::default()
+                }
+            })
+            .collect();
+        Ok(tables)
+    }
+
+    async fn fetch_table_details(&self, table: &TableMetadata) -> Result<TableMetadata> {
+        let columns = sqlx::query(
+            r#"
+                SELECT
+                    CAST(c.COLUMN_NAME AS CHAR) AS name,
+                    CAST(c.COLUMN_TYPE AS CHAR) AS data_type,
+                    CAST(c.IS_NULLABLE AS CHAR) AS is_nullable,
+                    CAST(c.COLUMN_DEFAULT AS CHAR) AS default_value,
+                    CAST(c.COLUMN_KEY AS CHAR) AS column_key,
+                    (
+                        SELECT CAST(CONCAT(k.REFERENCED_TABLE_SCHEMA, '.', k.REFERENCED_TABLE_NAME,
+                            '(', k.REFERENCED_COLUMN_NAME, ')') AS CHAR)
+                        FROM information_schema.KEY_COLUMN_USAGE k
+                        WHERE k.TABLE_SCHEMA = c.TABLE_SCHEMA
+                            AND k.TABLE_NAME = c.TABLE_NAME
+                            AND k.COLUMN_NAME = c.COLUMN_NAME
+                            AND k.REFERENCED_TABLE_NAME IS NOT NULL
+                        LIMIT 1
+                    ) AS references_to
+                FROM information_schema.COLUMNS c
+                WHERE c.TABLE_SCHEMA = ? AND c.TABLE_NAME = ?
+                ORDER BY c.ORDINAL_POSITION
+            "#,
+        )
+        .bind(&table.schema)
+        .bind(&table.name)
+        .fetch_all(self)
+        .await?
+        .into_iter()
+        .map(|r| ColumnMetadata {
+            name: r.get("name"),
+            data_type: r.get("data_type"),
+            nullable: r.get::<String, _>("is_nullable") == "YES",
+            default: r.get("default_value"),
+            primary_key: r.get::<String, _>("column_key") == "PRI",
+            references: r.get("references_to"),
+        })
+        .collect();
+
+        let constraints = sqlx::query(
+            r#"
+                SELECT CAST(CONSTRAINT_NAME AS CHAR) AS name
+                FROM information_schema.TABLE_CONSTRAINTS
+                WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ?
+                    AND CONSTRAINT_TYPE IN ('PRIMARY KEY', 'UNIQUE', 'FOREIGN KEY', 'CHECK')
+                ORDER BY CONSTRAINT_NAME
+            "#,
+        )
+        .bind(&table.schema)
+        .bind(&table.name)
+        .fetch_all(self)
+        .await?
+        .into_iter()
+        .map(|r| r.get("name"))
+        .collect();
+
+        // SHOW INDEX returns one row per indexed column.
+        let mut indexes: Vec<String> = Vec::new();
+        let index_rows = sqlx::query(&format!(
+            "SHOW INDEX FROM `{}`.`{}`",
+            table.schema.replace('`', "``"),
+            table.name.replace('`', "``")
+        ))
+        .fetch_all(self)
+        .await?;
+        for row in index_rows {
+            let name = mysql_text(&row, "Key_name");
+            if !indexes.contains(&name) {
+                indexes.push(name);
             }
-        });
+        }
 
-        let metadata = try_join_all(table_futures).await?;
-        Ok(metadata)
+        let triggers = sqlx::query(&format!(
+            "SHOW TRIGGERS FROM `{}` WHERE `Table` = ?",
+            table.schema.replace('`', "``")
+        ))
+        .bind(&table.name)
+        .fetch_all(self)
+        .await?
+        .into_iter()
+        .map(|r| r.get("Trigger"))
+        .collect();
+
+        Ok(TableMetadata {
+            columns,
+            constraints,
+            indexes,
+            triggers,
+            details_loaded: true,
+            ..table.clone()
+        })
+    }
+
+    /// MySQL has no sequences, custom types or extensions, only routines.
+    async fn fetch_objects(&self) -> Result<Vec<SchemaObject>> {
+        let rows = sqlx::query(
+            r#"
+                SELECT
+                    CAST(r.ROUTINE_SCHEMA AS CHAR) AS schema_name,
+                    CAST(r.ROUTINE_NAME AS CHAR) AS name,
+                    CAST(LOWER(r.ROUTINE_TYPE) AS CHAR) AS kind,
+                    CAST(CONCAT(
+                        '(',
+                        COALESCE((
+                            SELECT GROUP_CONCAT(
+                                CONCAT_WS(' ', p.PARAMETER_MODE, p.PARAMETER_NAME, p.DTD_IDENTIFIER)
+                                ORDER BY p.ORDINAL_POSITION SEPARATOR ', ')
+                            FROM information_schema.PARAMETERS p
+                            WHERE p.SPECIFIC_SCHEMA = r.ROUTINE_SCHEMA
+                                AND p.SPECIFIC_NAME = r.SPECIFIC_NAME
+                                AND p.ORDINAL_POSITION > 0
+                        ), ''),
+                        ')',
+                        IF(r.ROUTINE_TYPE = 'FUNCTION', CONCAT(' → ', r.DTD_IDENTIFIER), '')
+                    ) AS CHAR) AS detail,
+                    CAST(CONCAT('`', REPLACE(r.ROUTINE_SCHEMA, '`', '``'), '`.`',
+                        REPLACE(r.ROUTINE_NAME, '`', '``'), '`') AS CHAR) AS identity
+                FROM information_schema.ROUTINES r
+                WHERE r.ROUTINE_SCHEMA NOT IN ('mysql', 'information_schema', 'performance_schema', 'sys')
+                ORDER BY r.ROUTINE_SCHEMA, r.ROUTINE_NAME
+            "#,
+        )
+        .fetch_all(self)
+        .await?;
+
+        Ok(objects_from_rows(rows))
+    }
+}
+
+/// Reads a text column of a `SHOW` statement, which some servers return as binary.
+pub fn mysql_text<I>(row: &sqlx::mysql::MySqlRow, column: I) -> String
+where
+    I: sqlx::ColumnIndex<sqlx::mysql::MySqlRow> + Copy,
+{
+    row.try_get::<String, _>(column).unwrap_or_else(|_| {
+        row.try_get::<Vec<u8>, _>(column)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default()
+    })
+}
+
+/// Formats a byte count like Postgres' `pg_size_pretty`, e.g. `8192 bytes` or `16 kB`.
+pub fn human_size(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["kB", "MB", "GB", "TB"];
+    if bytes.abs() < 10 * 1024 {
+        return format!("{} bytes", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size.abs() >= 10.0 * 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
     }
+    format!("{} {}", size.round() as i64, UNITS[unit])
 }
 
 #[async_trait::async_trait]
-impl MetadataFetcher for MySqlPool {
-    async fn fetch_metadata(&self) -> Result<Vec<TableMetadata>> {
-        let rows = sqlx::query("SHOW TABLE STATUS").fetch_all(self).await?;
-
-        let mut tables = Vec::new();
-        for row in rows {
-            let table_name: String = row.get("Name");
-            let row_count: i64 = row.try_get("Rows").unwrap_or(0);
-            let estimated_size: String = {
-                let data_length: i64 = row.try_get("Data_length").unwrap_or(0);
-                let index_length: i64 = row.try_get("Index_length").unwrap_or(0);
-                format!("{} bytes", data_length + index_length)
-            };
-            let table_type: String = row.try_get("Comment").unwrap_or("".to_string());
+impl MetadataFetcher for SqlitePool {
+    /// Row counts come from `sqlite_stat1` (written by `ANALYZE`) and sizes
+    /// from the `dbstat` virtual table; both are optional in SQLite.
+    async fn fetch_tables(&self) -> Result<Vec<TableMetadata>> {
+        let rows = sqlx::query(
+            r#"
+                SELECT name, type FROM sqlite_master
+                WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite\_%' ESCAPE '\'
+                ORDER BY name
+            "#,
+        )
+        .fetch_all(self)
+        .await?;
 
-            let columns = sqlx::query(&format!("SHOW COLUMNS FROM `{}`", table_name))
-                .fetch_all(self)
-                .await?
-                .into_iter()
-                .map(|r| r.get("Field"))
-                .collect();
+        let row_estimates = sqlite_row_estimates(self).await;
+        let sizes = sqlite_table_sizes(self).await;
+
+        let tables = rows
+            .into_iter()
+            .map(|row| {
+                let name: String = row.get("name");
+                TableMetadata {
+                    schema: "main".to_string(),
+                    row_count: row_estimates.get(&name).copied().unwrap_or(0),
+                    estimated_size: match &sizes {
+                        Some(sizes) => human_size(sizes.get(&name).copied().unwrap_or(0)),
+                        None => "N/A".to_string(),
+                    },
+                    table_type: row.get("type"),
+                    name,
+                    ..TableMetadata::default()
+                }
+            })
+            .collect();
+        Ok(tables)
+    }
+
+    async fn fetch_table_details(&self, table: &TableMetadata) -> Result<TableMetadata> {
+        let table_name = &table.name;
 
-            let triggers = sqlx::query("SHOW TRIGGERS WHERE `Table` = ?")
-                .bind(&table_name)
+        let foreign_keys: Vec<(String, String, Option<String>)> =
+            sqlx::query(r#"SELECT "from", "table", "to" FROM pragma_foreign_key_list(?)"#)
+                .bind(table_name)
                 .fetch_all(self)
                 .await?
-                .into_iter()
-                .map(|r| r.get("Trigger"))
+                .iter()
+                .map(|r| (r.get("from"), r.get("table"), r.get("to")))
                 .collect();
 
-            tables.push(TableMetadata {
-                name: table_name,
-                columns,
-                constraints: vec![],
-                indexes: vec![],
-                rls_policies: vec![],
-                rules: vec![],
-                triggers,
-                row_count,
-                estimated_size,
-                table_type,
-                table_data: None,
-            });
-        }
-        Ok(tables)
-    }
-}
+        let columns_rows = sqlx::query(
+            r#"SELECT name, type, "notnull", dflt_value, pk FROM pragma_table_info(?)"#,
+        )
+        .bind(table_name)
+        .fetch_all(self)
+        .await?;
+        let columns =
+            columns_rows
+                .iter()
+                .map(|r| {
+                    let name: String = r.get("name");
+                    // `to` is NULL when the foreign key targets the primary key.
+                    let references = foreign_keys.iter().find(|(from, _, _)| *from == name).map(
+                        |(_, table, to)| match to {
+                            Some(to) => format!("{}({})", table, to),
+                            None => table.clone(),
+                        },
+                    );
+                    ColumnMetadata {
+                        data_type: r.get("type"),
+                        nullable: r.get::<i64, _>("notnull") == 0,
+                        default: r.get("dflt_value"),
+                        primary_key: r.get::<i64, _>("pk") > 0,
+                        references,
+                        name,
+                    }
+                })
+                .collect();
 
-#[async_trait::async_trait]
-impl MetadataFetcher for SqlitePool {
-    async fn fetch_metadata(&self) -> Result<Vec<TableMetadata>> {
-        let rows = sqlx::query("SELECT name FROM sqlite_master WHERE type='table'")
+        let indexes_rows = sqlx::query("SELECT name FROM pragma_index_list(?)")
+            .bind(table_name)
             .fetch_all(self)
             .await?;
+        let indexes = indexes_rows.iter().map(|r| r.get("name")).collect();
 
-        let mut tables = Vec::new();
-        for row in rows {
-            let table_name: String = row.get("name");
+        let mut constraints: Vec<String> = Vec::new();
+        let fk_rows = sqlx::query(
+            r#"
+                SELECT id, group_concat("from", ', ') AS columns, "table",
+                    group_concat("to", ', ') AS targets
+                FROM pragma_foreign_key_list(?)
+                GROUP BY id, "table"
+                ORDER BY id
+            "#,
+        )
+        .bind(table_name)
+        .fetch_all(self)
+        .await?;
+        for row in fk_rows {
+            let columns: String = row.get("columns");
+            let target: String = row.get("table");
+            let targets: Option<String> = row.get("targets");
+            constraints.push(match targets {
+                Some(targets) => format!("FK ({}) → {}({})", columns, target, targets),
+                None => format!("FK ({}) → {}", columns, target),
+            });
+        }
 
-            let columns_rows = sqlx::query(&format!("PRAGMA table_info('{}')", table_name))
+        let triggers_rows =
+            sqlx::query("SELECT name FROM sqlite_master WHERE type='trigger' AND tbl_name=?")
+                .bind(table_name)
                 .fetch_all(self)
                 .await?;
-            let columns = columns_rows.iter().map(|r| r.get("name")).collect();
+        let triggers = triggers_rows.iter().map(|r| r.get("name")).collect();
 
-            let indexes_rows = sqlx::query(&format!("PRAGMA index_list('{}')", table_name))
-                .fetch_all(self)
-                .await?;
-            let indexes = indexes_rows.iter().map(|r| r.get("name")).collect();
-
-            let triggers_rows =
-                sqlx::query("SELECT name FROM sqlite_master WHERE type='trigger' AND tbl_name=?")
-                    .bind(&table_name)
-                    .fetch_all(self)
-                    .await?;
-            let triggers = triggers_rows.iter().map(|r| r.get("name")).collect();
-
-            tables.push(TableMetadata {
-                name: table_name,
-                columns,
-                constraints: vec![],
-                indexes,
-                rls_policies: vec![],
-                rules: vec![],
-                triggers,
-                row_count: 0,
-                estimated_size: "N/A".to_string(),
-                table_type: "table".to_string(),
-                table_data: None,
-            });
+        Ok(TableMetadata {
+            columns,
+            constraints,
+            indexes,
+            triggers,
+            details_loaded: true,
+            ..table.clone()
+        })
+    }
+}
+
+/// Row counts recorded by `ANALYZE`. The first number of a `stat` entry is
+/// the row count of the table (`idx` is NULL) or of one of its indexes,
+/// which is smaller for partial indexes. The table's own entry wins, then
+/// the largest index. Empty when the database was never analyzed.
+async fn sqlite_row_estimates(pool: &SqlitePool) -> HashMap<String, i64> {
+    let rows = sqlx::query("SELECT tbl, idx, stat FROM sqlite_stat1")
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default();
+
+    let mut estimates: HashMap<String, (bool, i64)> = HashMap::new();
+    for row in rows {
+        let (Ok(table), Ok(index), Ok(stat)) = (
+            row.try_get::<String, _>("tbl"),
+            row.try_get::<Option<String>, _>("idx"),
+            row.try_get::<String, _>("stat"),
+        ) else {
+            continue;
+        };
+        let Some(count) = stat.split_whitespace().next().and_then(|n| n.parse().ok()) else {
+            continue;
+        };
+        let whole_table = index.is_none();
+        let estimate = estimates.entry(table).or_insert((whole_table, count));
+        if whole_table || (!estimate.0 && count > estimate.1) {
+            *estimate = (whole_table, count);
         }
-        Ok(tables)
     }
+    estimates
+        .into_iter()
+        .map(|(table, (_, count))| (table, count))
+        .collect()
 }
 
-pub async fn fetch_all_table_metadata(pool: &DbPool) -> Result<Vec<TableMetadata>> {
-    let metadata = match pool {
-        DbPool::Postgres(pg) => pg.fetch_metadata().await?,
-        DbPool::MySQL(mysql) => mysql.fetch_metadata().await?,
-        DbPool::SQLite(sqlite) => sqlite.fetch_metadata().await?,
-    };
+/// Bytes used by each table and its indexes, or `None` when SQLite was built
+/// without the `dbstat` virtual table.
+async fn sqlite_table_sizes(pool: &SqlitePool) -> Option<HashMap<String, i64>> {
+    let rows = sqlx::query(
+        r#"
+            SELECT m.tbl_name AS table_name, SUM(d.pgsize) AS size
+            FROM dbstat d
+            JOIN sqlite_master m ON m.name = d.name
+            GROUP BY m.tbl_name
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .ok()?;
 
-    if metadata.is_empty() {
-        println!("No table metadata found.");
-    } else {
-        println!("Found {} tables.", metadata.len());
+    Some(
+        rows.iter()
+            .map(|row| (row.get("table_name"), row.get("size")))
+            .collect(),
+    )
+}
+
+/// Replaces the estimated row counts of SQLite tables with `COUNT(*)`.
+async fn sqlite_count_rows(pool: &SqlitePool, tables: &mut [TableMetadata]) -> Result<()> {
+    for table in tables.iter_mut().filter(|t| t.table_type == "table") {
+        let sql = format!(
+            "SELECT COUNT(*) AS count FROM \"{}\"",
+            table.name.replace('"', "\"\"")
+        );
+        table.row_count = sqlx::query(&sql).fetch_one(pool).await?.get("count");
     }
+    Ok(())
+}
+
+/// Lists the tables of a connection without their per-table details.
+///
+/// With `exact_counts`, SQLite tables are counted with `COUNT(*)` instead of
+/// relying on `ANALYZE` statistics, which most files do not have.
+pub async fn fetch_all_table_metadata(
+    pool: &DbPool,
+    exact_counts: bool,
+) -> Result<Vec<TableMetadata>> {
+    let metadata = match pool {
+        DbPool::Postgres(pg) => pg.fetch_tables().await?,
+        DbPool::MySQL(mysql) => mysql.fetch_tables().await?,
+        DbPool::SQLite(sqlite) => {
+            let mut tables = sqlite.fetch_tables().await?;
+            if exact_counts {
+                sqlite_count_rows(sqlite, &mut tables).await?;
+            }
+            tables
+        }
+    };
 
     Ok(metadata)
 }
 
-async fn get_pg_columns(pool: &PgPool, table: &str) -> sqlx::Result<Vec<String>> {
-    let rows = sqlx::query("SELECT column_name FROM information_schema.columns WHERE table_schema = 'public' AND table_name = $1")
-        .bind(table)
-        .fetch_all(pool)
-        .await?;
-    Ok(rows.into_iter().map(|r| r.get("column_name")).collect())
+/// Lists the schema objects shown next to the tables. SQLite has none.
+pub async fn fetch_schema_objects(pool: &DbPool) -> Result<Vec<SchemaObject>> {
+    match pool {
+        DbPool::Postgres(pg) => pg.fetch_objects().await,
+        DbPool::MySQL(mysql) => mysql.fetch_objects().await,
+        DbPool::SQLite(sqlite) => sqlite.fetch_objects().await,
+    }
 }
 
-async fn get_pg_constraints(pool: &PgPool, table: &str) -> sqlx::Result<Vec<String>> {
-    let rows = sqlx::query(
-        "SELECT constraint_name FROM information_schema.table_constraints WHERE table_name = $1 AND constraint_type != 'CHECK'",
-    )
-    .bind(table)
-    .fetch_all(pool)
-    .await?;
-    Ok(rows.into_iter().map(|r| r.get("constraint_name")).collect())
+/// Loads the details of a single table, e.g. when its sidebar node is expanded.
+pub async fn fetch_table_details(pool: &DbPool, table: &TableMetadata) -> Result<TableMetadata> {
+    match pool {
+        DbPool::Postgres(pg) => pg.fetch_table_details(table).await,
+        DbPool::MySQL(mysql) => mysql.fetch_table_details(table).await,
+        DbPool::SQLite(sqlite) => sqlite.fetch_table_details(table).await,
+    }
 }
 
-async fn get_pg_indexes(pool: &PgPool, table: &str) -> sqlx::Result<Vec<String>> {
-    let rows = sqlx::query("SELECT indexname FROM pg_indexes WHERE tablename = $1")
-        .bind(table)
-        .fetch_all(pool)
-        .await?;
-    Ok(rows.into_iter().map(|r| r.get("indexname")).collect())
+/// Loads the details of several tables, e.g. the expanded ones after a refresh.
+pub async fn fetch_tables_details(
+    pool: &DbPool,
+    tables: &[TableMetadata],
+) -> Result<Vec<TableMetadata>> {
+    match pool {
+        DbPool::Postgres(pg) => pg.fetch_tables_details(tables).await,
+        DbPool::MySQL(mysql) => mysql.fetch_tables_details(tables).await,
+        DbPool::SQLite(sqlite) => sqlite.fetch_tables_details(tables).await,
+    }
 }
 
-async fn get_pg_rls_policies(pool: &PgPool, table: &str) -> sqlx::Result<Vec<String>> {
-    let rows = sqlx::query("SELECT policyname FROM pg_policies WHERE tablename = $1")
-        .bind(table)
-        .fetch_all(pool)
-        .await?;
-    Ok(rows.into_iter().map(|r| r.get("policyname")).collect())
+/// Fetches the column and index names of every table in one query, for the
+/// sidebar filter to match tables whose details are not loaded.
+pub async fn fetch_search_names(pool: &DbPool) -> Result<ByTable<String>> {
+    let rows: Vec<(String, String, String)> = match pool {
+        DbPool::Postgres(pg) => {
+            let sql = format!(
+                r#"
+                    SELECT n.nspname::TEXT, c.relname::TEXT, a.attname::TEXT
+                    FROM pg_attribute a
+                    JOIN pg_class c ON c.oid = a.attrelid
+                    JOIN pg_namespace n ON n.oid = c.relnamespace
+                    WHERE a.attnum > 0 AND NOT a.attisdropped
+                        AND {PG_TABLE_KINDS} AND {PG_USER_SCHEMAS}
+                    UNION ALL
+                    SELECT n.nspname::TEXT, c.relname::TEXT, i.relname::TEXT
+                    FROM pg_index x
+                    JOIN pg_class i ON i.oid = x.indexrelid
+                    JOIN pg_class c ON c.oid = x.indrelid
+                    JOIN pg_namespace n ON n.oid = c.relnamespace
+                    WHERE {PG_USER_SCHEMAS}
+                "#
+            );
+            sqlx::query_as(&sql).fetch_all(pg).await?
+        }
+        DbPool::MySQL(mysql) => {
+            sqlx::query_as(
+                r#"
+                    SELECT CAST(TABLE_SCHEMA AS CHAR), CAST(TABLE_NAME AS CHAR),
+                        CAST(COLUMN_NAME AS CHAR)
+                    FROM information_schema.COLUMNS
+                    WHERE TABLE_SCHEMA NOT IN ('mysql', 'information_schema', 'performance_schema', 'sys')
+                    UNION ALL
+                    SELECT DISTINCT CAST(TABLE_SCHEMA AS CHAR), CAST(TABLE_NAME AS CHAR),
+                        CAST(INDEX_NAME AS CHAR)
+                    FROM information_schema.STATISTICS
+                    WHERE TABLE_SCHEMA NOT IN ('mysql', 'information_schema', 'performance_schema', 'sys')
+                "#,
+            )
+            .fetch_all(mysql)
+            .await?
+        }
+        DbPool::SQLite(sqlite) => {
+            sqlx::query_as(
+                r#"
+                    SELECT 'main', m.name, p.name
+                    FROM sqlite_master m
+                    JOIN pragma_table_info(m.name) p
+                    WHERE m.type IN ('table', 'view')
+                    UNION ALL
+                    SELECT 'main', tbl_name, name FROM sqlite_master WHERE type = 'index'
+                "#,
+            )
+            .fetch_all(sqlite)
+            .await?
+        }
+    };
+
+    let mut names: ByTable<String> = HashMap::new();
+    for (schema, table, name) in rows {
+        names.entry((schema, table)).or_default().push(name);
+    }
+    Ok(names)
 }
 
-async fn get_pg_rules(pool: &PgPool, table: &str) -> sqlx::Result<Vec<String>> {
-    let rows = sqlx::query("SELECT rulename FROM pg_rules WHERE tablename = $1")
-        .bind(table)
-        .fetch_all(pool)
-        .await?;
-    Ok(rows.into_iter().map(|r| r.get("rulename")).collect())
+/// Narrows the sidebar tree to the tables, views, columns, indexes and
+/// schema objects whose names fuzzy-match a pattern. Matching nodes keep
+/// their parents, and a matching table keeps all of its children. With an
+/// empty pattern every node is kept.
+#[derive(Debug, Default)]
+pub struct TreeFilter {
+    pattern: String,
+    /// Sidebar paths of the matching nodes, in tree order.
+    pub hits: Vec<Vec<String>>,
 }
 
-async fn get_pg_triggers(pool: &PgPool, table: &str) -> sqlx::Result<Vec<String>> {
-    let rows = sqlx::query("SELECT tgname FROM pg_trigger JOIN pg_class ON tgrelid = pg_class.oid WHERE relname = $1 AND NOT tgisinternal")
-        .bind(table)
-        .fetch_all(pool)
-        .await?;
-    Ok(rows.into_iter().map(|r| r.get("tgname")).collect())
+impl TreeFilter {
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            hits: Vec::new(),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.pattern.trim().is_empty()
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.is_active() && fuzzy_match(&self.pattern, name).is_some()
+    }
+
+    /// Labels the node at `path` whose text starts with `name`, highlighting
+    /// the matched characters and recording a hit. Returns whether it matched.
+    fn visit(&mut self, path: Vec<String>, name: &str, text: &str) -> (bool, Text<'static>) {
+        let positions = match self.is_active() {
+            true => fuzzy_match(&self.pattern, name),
+            false => None,
+        };
+        let Some(positions) = positions else {
+            return (false, Text::from(text.to_string()));
+        };
+
+        self.hits.push(path);
+        let label = match text.strip_prefix(name) {
+            Some(suffix) => highlight_match(name, &positions, suffix),
+            None => Line::from(text.to_string()),
+        };
+        (true, Text::from(label))
+    }
 }
 
-pub fn build_category_node(
-    parent: &str,
+/// Builds the `label` node of the table at `path`. Unless `keep_all` is set,
+/// only children matching the filter are kept, and only `searchable` ones can.
+fn category_node(
+    path: &[String],
     label: &str,
-    items: &[String],
-) -> TreeItem<'static, String> {
+    items: &[(String, String)],
+    searchable: bool,
+    keep_all: bool,
+    filter: &mut TreeFilter,
+) -> Option<TreeItem<'static, String>> {
+    let parent = path.last().map(String::as_str).unwrap_or_default();
     let id = format!("{}_{}", parent, label);
-    if items.is_empty() {
-        TreeItem::new_leaf(id.clone(), label.to_string())
+    let children: Vec<_> = items
+        .iter()
+        .filter_map(|(name, text)| {
+            let child_id = format!("{}_{}", id, name);
+            let (matched, text) = if searchable {
+                let child_path = [path, &[id.clone(), child_id.clone()]].concat();
+                filter.visit(child_path, name, text)
+            } else {
+                (false, Text::from(text.clone()))
+            };
+            (matched || keep_all).then(|| TreeItem::new_leaf(child_id, text))
+        })
+        .collect();
+
+    if children.is_empty() {
+        keep_all.then(|| TreeItem::new_leaf(id, label.to_string()))
+    } else {
+        Some(TreeItem::new(id, label.to_string(), children).unwrap())
+    }
+}
+
+/// Builds the node of a table under `parent`, or `None` when neither the
+/// table nor any of its columns or indexes match the filter.
+pub fn table_to_tree_item(
+    table: &TableMetadata,
+    parent: &[String],
+    filter: &mut TreeFilter,
+) -> Option<TreeItem<'static, String>> {
+    let id = table.name.clone();
+    let path = [parent, std::slice::from_ref(&id)].concat();
+    let text = format!(
+        "{} ({} row{})",
+        id,
+        table.row_count,
+        if table.row_count == 0 || table.row_count == 1 {
+            ""
+        } else {
+            "s"
+        }
+    );
+    let (matched, label) = filter.visit(path.clone(), &table.name, &text);
+    let keep_all = matched || !filter.is_active();
+
+    // A placeholder child keeps the node expandable until its details arrive.
+    let children: Vec<_> = if !table.details_loaded {
+        if !keep_all {
+            let names = table.search_names.as_deref().unwrap_or_default();
+            if !names.iter().any(|name| filter.matches(name)) {
+                return None;
+            }
+            // Its matching columns show once the table is expanded.
+            filter.hits.push(path.clone());
+        }
+        vec![TreeItem::new_leaf(
+            format!("{}_loading", id),
+            "⏳ Loading…".to_string(),
+        )]
     } else {
-        let children = items
+        let named = |items: &[String]| -> Vec<(String, String)> {
+            items.iter().map(|i| (i.clone(), i.clone())).collect()
+        };
+        let columns: Vec<(String, String)> = table
+            .columns
             .iter()
-            .map(|item| {
-                let child_id = format!("{}_{}", id, item);
-                TreeItem::new_leaf(child_id, item.clone())
-            })
+            .map(|column| (column.name.clone(), column.label()))
             .collect();
 
-        TreeItem::new(id, label.to_string(), children).unwrap()
+        [
+            ("Columns", columns, true),
+            ("Constraints", named(&table.constraints), false),
+            ("Indexes", named(&table.indexes), true),
+            ("RLS Policies", named(&table.rls_policies), false),
+            ("Rules", named(&table.rules), false),
+            ("Triggers", named(&table.triggers), false),
+        ]
+        .into_iter()
+        .filter_map(|(label, items, searchable)| {
+            category_node(&path, label, &items, searchable, keep_all, filter)
+        })
+        .collect()
+    };
+
+    if children.is_empty() {
+        return None;
+    }
+    Some(TreeItem::new(id, label, children).unwrap())
+}
+
+/// Identifiers of the groups under a schema node that hold tables and views.
+pub const TABLES_GROUP: &str = "tables";
+pub const VIEWS_GROUP: &str = "views";
+pub const MATERIALIZED_VIEWS_GROUP: &str = "materialized_views";
+
+/// Extensions belong to the database, so their group sits next to the schema
+/// nodes. The leading colon keeps it apart from a schema of the same name.
+pub const EXTENSIONS_GROUP: &str = ":extensions";
+
+/// Identifier and label of the schema group a table or view is listed in.
+pub fn table_group(table: &TableMetadata) -> (&'static str, &'static str) {
+    match table.table_type.as_str() {
+        "view" | "system view" => (VIEWS_GROUP, "Views"),
+        "materialized view" => (MATERIALIZED_VIEWS_GROUP, "Materialized Views"),
+        _ => (TABLES_GROUP, "Tables"),
     }
 }
 
-pub fn metadata_to_tree_items(metadata: &[TableMetadata]) -> Vec<TreeItem<'static, String>> {
-    metadata
+fn group_node(
+    id: &str,
+    label: &str,
+    children: Vec<TreeItem<'static, String>>,
+) -> TreeItem<'static, String> {
+    let label = format!("{} ({})", label, children.len());
+    TreeItem::new(id.to_string(), Text::from(label), children)
+        .expect("object identifiers are unique within a group")
+}
+
+fn object_to_tree_item(
+    object: &SchemaObject,
+    parent: &[String],
+    filter: &mut TreeFilter,
+) -> Option<TreeItem<'static, String>> {
+    let path = [parent, std::slice::from_ref(&object.identity)].concat();
+    let (matched, label) = filter.visit(path, &object.name, &object.label());
+    (matched || !filter.is_active()).then(|| TreeItem::new_leaf(object.identity.clone(), label))
+}
+
+/// Builds one node per schema under `parent`, with a group for each kind of
+/// object it holds, followed by the database's extensions.
+pub fn metadata_to_tree_items(
+    metadata: &[TableMetadata],
+    objects: &[SchemaObject],
+    parent: &[String],
+    filter: &mut TreeFilter,
+) -> Vec<TreeItem<'static, String>> {
+    let mut schemas: Vec<&str> = metadata
         .iter()
-        .map(|table| {
-            let id = table.name.clone();
-
-            let children = vec![
-                build_category_node(&id, "Columns", &table.columns),
-                build_category_node(&id, "Constraints", &table.constraints),
-                build_category_node(&id, "Indexes", &table.indexes),
-                build_category_node(&id, "RLS Policies", &table.rls_policies),
-                build_category_node(&id, "Rules", &table.rules),
-                build_category_node(&id, "Triggers", &table.triggers),
-            ];
-
-            TreeItem::new(
-                id.clone(),
-                Text::from(format!(
-                    "{} ({} row{})",
-                    id,
-                    table.row_count,
-                    if table.row_count == 0 || table.row_count == 1 {
-                        ""
-                    } else {
-                        "s"
-                    }
-                )),
-                children,
-            )
-            .unwrap()
+        .map(|table| table.schema.as_str())
+        .chain(
+            objects
+                .iter()
+                .filter(|object| object.kind != ObjectKind::Extension)
+                .map(|object| object.schema.as_str()),
+        )
+        .collect();
+    schemas.sort();
+    schemas.dedup();
+
+    let table_groups = [
+        (TABLES_GROUP, "Tables"),
+        (VIEWS_GROUP, "Views"),
+        (MATERIALIZED_VIEWS_GROUP, "Materialized Views"),
+    ];
+    let object_groups = [
+        ObjectKind::Function,
+        ObjectKind::Procedure,
+        ObjectKind::Sequence,
+        ObjectKind::EnumType,
+    ]
+    .map(|kind| kind.group());
+
+    let mut items: Vec<TreeItem<'static, String>> = schemas
+        .into_iter()
+        .filter_map(|schema| {
+            let tables: Vec<&TableMetadata> =
+                metadata.iter().filter(|t| t.schema == schema).collect();
+
+            let mut groups = Vec::new();
+            for (id, label) in table_groups {
+                let path = [parent, &[schema.to_string(), id.to_string()]].concat();
+                let children: Vec<_> = tables
+                    .iter()
+                    .filter(|table| table_group(table).0 == id)
+                    .filter_map(|table| table_to_tree_item(table, &path, filter))
+                    .collect();
+                if !children.is_empty() {
+                    groups.push(group_node(id, label, children));
+                }
+            }
+            for (id, label) in object_groups {
+                let path = [parent, &[schema.to_string(), id.to_string()]].concat();
+                let children: Vec<_> = objects
+                    .iter()
+                    .filter(|object| object.schema == schema && object.kind.group().0 == id)
+                    .filter_map(|object| object_to_tree_item(object, &path, filter))
+                    .collect();
+                if !children.is_empty() {
+                    groups.push(group_node(id, label, children));
+                }
+            }
+            if groups.is_empty() {
+                return None;
+            }
+
+            let label = format!(
+                "{} ({} table{})",
+                schema,
+                tables.len(),
+                if tables.len() == 1 { "" } else { "s" }
+            );
+            Some(TreeItem::new(schema.to_string(), Text::from(label), groups).unwrap())
         })
-        .collect()
+        .collect();
+
+    let (id, label) = ObjectKind::Extension.group();
+    let path = [parent, &[id.to_string()]].concat();
+    let extensions: Vec<_> = objects
+        .iter()
+        .filter(|object| object.kind == ObjectKind::Extension)
+        .filter_map(|object| object_to_tree_item(object, &path, filter))
+        .collect();
+    if !extensions.is_empty() {
+        items.push(group_node(id, label, extensions));
+    }
+
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::pool::sqlite_test_pool;
+
+    fn table(schema: &str, name: &str) -> TableMetadata {
+        TableMetadata {
+            schema: schema.to_string(),
+            name: name.to_string(),
+            columns: vec![],
+            constraints: vec![],
+            indexes: vec![],
+            rls_policies: vec![],
+            rules: vec![],
+            triggers: vec![],
+            row_count: 0,
+            estimated_size: String::new(),
+            table_type: "table".to_string(),
+            table_data: None,
+            details_loaded: true,
+            search_names: None,
+        }
+    }
+
+    #[test]
+    fn test_tree_groups_tables_by_schema() {
+        let metadata = vec![
+            table("audit", "events"),
+            table("billing", "events"),
+            table("billing", "invoices"),
+        ];
+        let items = metadata_to_tree_items(&metadata, &[], &[], &mut TreeFilter::default());
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].identifier(), "audit");
+        assert_eq!(items[1].identifier(), "billing");
+
+        let tables = &items[1].children()[0];
+        assert_eq!(tables.identifier(), TABLES_GROUP);
+        assert_eq!(tables.children().len(), 2);
+        assert_eq!(tables.children()[0].identifier(), "events");
+    }
+
+    #[test]
+    fn test_tree_groups_views_and_objects() {
+        let mut view = table("public", "active_users");
+        view.table_type = "view".to_string();
+        let object = |kind, schema: &str, name: &str, identity: &str| SchemaObject {
+            schema: schema.to_string(),
+            name: name.to_string(),
+            kind,
+            detail: String::new(),
+            identity: identity.to_string(),
+        };
+        let objects = vec![
+            object(
+                ObjectKind::Function,
+                "public",
+                "add",
+                "add(integer,integer)",
+            ),
+            object(
+                ObjectKind::Function,
+                "public",
+                "add",
+                "add(numeric,numeric)",
+            ),
+            object(ObjectKind::EnumType, "public", "mood", "public.mood"),
+            object(ObjectKind::Extension, "public", "pgcrypto", "pgcrypto"),
+        ];
+        let items = metadata_to_tree_items(
+            &[table("public", "users"), view],
+            &objects,
+            &[],
+            &mut TreeFilter::default(),
+        );
+
+        let groups: Vec<&str> = items[0]
+            .children()
+            .iter()
+            .map(|group| group.identifier().as_str())
+            .collect();
+        assert_eq!(groups, [TABLES_GROUP, VIEWS_GROUP, "functions", "types"]);
+        assert_eq!(items[0].children()[2].children().len(), 2);
+        assert_eq!(items[1].identifier(), EXTENSIONS_GROUP);
+    }
+
+    #[test]
+    fn test_unloaded_table_shows_loading_placeholder() {
+        let mut users = table("public", "users");
+        users.details_loaded = false;
+        let item = table_to_tree_item(&users, &[], &mut TreeFilter::default()).unwrap();
+
+        assert_eq!(item.children().len(), 1);
+        assert_eq!(item.children()[0].identifier(), "users_loading");
+    }
+
+    #[test]
+    fn test_filter_matches_unloaded_tables_by_search_names() {
+        let mut users = table("public", "users");
+        users.details_loaded = false;
+        let mut filter = TreeFilter::new("mail");
+        assert!(table_to_tree_item(&users, &[], &mut filter).is_none());
+
+        users.search_names = Some(vec!["id".to_string(), "email".to_string()]);
+        let mut filter = TreeFilter::new("mail");
+        assert!(table_to_tree_item(&users, &[], &mut filter).is_some());
+        assert_eq!(filter.hits, [vec!["users".to_string()]]);
+    }
+
+    #[test]
+    fn test_filter_prunes_tree_to_matches() {
+        let mut orders = table("public", "orders");
+        orders.columns = vec![
+            ColumnMetadata {
+                name: "id".to_string(),
+                ..Default::default()
+            },
+            ColumnMetadata {
+                name: "customer_email".to_string(),
+                ..Default::default()
+            },
+        ];
+        orders.indexes = vec!["orders_pkey".to_string()];
+        let metadata = vec![orders, table("public", "users"), table("audit", "events")];
+
+        let mut filter = TreeFilter::new("email");
+        let items = metadata_to_tree_items(&metadata, &[], &["db".to_string()], &mut filter);
+
+        assert_eq!(items.len(), 1);
+        let tables = &items[0].children()[0];
+        assert_eq!(tables.children().len(), 1);
+        let orders = &tables.children()[0];
+        assert_eq!(orders.children().len(), 1);
+        assert_eq!(orders.children()[0].children().len(), 1);
+        assert_eq!(
+            filter.hits,
+            vec![vec![
+                "db".to_string(),
+                "public".to_string(),
+                TABLES_GROUP.to_string(),
+                "orders".to_string(),
+                "orders_Columns".to_string(),
+                "orders_Columns_customer_email".to_string(),
+            ]]
+        );
+
+        // A matching table keeps all of its children.
+        let mut filter = TreeFilter::new("ordrs");
+        let items = metadata_to_tree_items(&metadata, &[], &[], &mut filter);
+        let orders = &items[0].children()[0].children()[0];
+        assert_eq!(orders.children().len(), 6);
+        assert_eq!(filter.hits.len(), 2);
+    }
+
+    #[test]
+    fn test_column_label() {
+        let id = ColumnMetadata {
+            name: "id".to_string(),
+            data_type: "bigint".to_string(),
+            nullable: false,
+            default: None,
+            primary_key: true,
+            references: None,
+        };
+        assert_eq!(id.label(), "id  bigint  PK  NOT NULL");
+
+        let user_id = ColumnMetadata {
+            name: "user_id".to_string(),
+            data_type: "integer".to_string(),
+            nullable: true,
+            default: Some("0".to_string()),
+            primary_key: false,
+            references: Some("public.users(id)".to_string()),
+        };
+        assert_eq!(
+            user_id.label(),
+            "user_id  integer  DEFAULT 0  → public.users(id)"
+        );
+    }
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(0), "0 bytes");
+        assert_eq!(human_size(8192), "8192 bytes");
+        assert_eq!(human_size(16 * 1024), "16 kB");
+        assert_eq!(human_size(128 * 1024 * 1024), "128 MB");
+        assert_eq!(human_size(3 * 1024 * 1024 * 1024 * 1024 * 1024), "3072 TB");
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_views_and_foreign_keys() {
+        let db = sqlite_test_pool(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT NOT NULL);
+             CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INT REFERENCES users(id));
+             CREATE VIEW recent AS SELECT * FROM posts;
+             INSERT INTO users (email) VALUES ('a'), ('b');",
+        )
+        .await;
+
+        let tables = fetch_all_table_metadata(&db, true).await.unwrap();
+        let names: Vec<(&str, &str)> = tables
+            .iter()
+            .map(|t| (t.name.as_str(), t.table_type.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [("posts", "table"), ("recent", "view"), ("users", "table")]
+        );
+        assert_eq!(tables[2].row_count, 2);
+
+        let posts = fetch_table_details(&db, &tables[0]).await.unwrap();
+        assert_eq!(posts.constraints, ["FK (user_id) → users(id)"]);
+        assert_eq!(posts.columns[1].references.as_deref(), Some("users(id)"));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_details_bind_the_table_name() {
+        let db =
+            sqlite_test_pool(r#"CREATE TABLE "it's" (n INT); CREATE INDEX by_n ON "it's" (n);"#)
+                .await;
+
+        let tables = fetch_all_table_metadata(&db, false).await.unwrap();
+        let loaded = fetch_tables_details(&db, &tables).await.unwrap();
+        assert_eq!(loaded[0].name, "it's");
+        assert_eq!(loaded[0].indexes, ["by_n"]);
+        assert_eq!(loaded[0].columns[0].name, "n");
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_estimates_ignore_partial_indexes() {
+        let db = sqlite_test_pool(
+            "CREATE TABLE t (n INT);
+             CREATE INDEX t_all ON t (n);
+             CREATE INDEX t_some ON t (n) WHERE n > 5;
+             WITH RECURSIVE c(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM c WHERE i < 10)
+             INSERT INTO t SELECT i FROM c;
+             ANALYZE;",
+        )
+        .await;
+
+        let tables = fetch_all_table_metadata(&db, false).await.unwrap();
+        assert_eq!(tables[0].row_count, 10);
+    }
 }
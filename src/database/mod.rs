@@ -1,5 +1,10 @@
+pub mod connection;
 pub mod connector;
+pub mod credentials;
 pub mod db_list;
+pub mod ddl;
 pub mod detector;
 pub mod fetch;
 pub mod pool;
+pub mod profiles;
+pub mod properties;
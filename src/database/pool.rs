@@ -1,6 +1,15 @@
-use sqlx::{mysql::MySqlPool, postgres::PgPool, sqlite::SqlitePool};
+use sqlx::{
+    mysql::MySqlPool,
+    pool::PoolOptions,
+    postgres::PgPool,
+    sqlite::{SqliteConnectOptions, SqlitePool},
+};
+use std::io;
+use std::str::FromStr;
+use std::time::Duration;
 
 use super::connector::{ConnectionDetails, DatabaseType};
+use super::credentials::fill_missing;
 
 #[derive(Debug, Clone)]
 pub enum DbPool {
@@ -9,26 +18,80 @@ pub enum DbPool {
     SQLite(SqlitePool),
 }
 
+impl DbPool {
+    pub fn db_type(&self) -> DatabaseType {
+        match self {
+            DbPool::Postgres(_) => DatabaseType::PostgreSQL,
+            DbPool::MySQL(_) => DatabaseType::MySQL,
+            DbPool::SQLite(_) => DatabaseType::SQLite,
+        }
+    }
+}
+
 pub async fn pool(
     db_type: DatabaseType,
     details: &ConnectionDetails,
 ) -> Result<DbPool, sqlx::Error> {
+    let mut details = details.clone();
+    fill_missing(&mut details);
+
     let conn_str = &details.connection_string();
+    let connect_timeout = details
+        .param("connect_timeout")
+        .and_then(|secs| secs.parse::<u64>().ok())
+        .map(Duration::from_secs);
 
     let pool = match db_type {
         DatabaseType::PostgreSQL => {
-            let pool = PgPool::connect(conn_str).await?;
+            let pool = with_timeout(connect_timeout, PoolOptions::new().connect(conn_str)).await?;
             DbPool::Postgres(pool)
         }
         DatabaseType::MySQL => {
-            let pool = MySqlPool::connect(conn_str).await?;
+            let pool = with_timeout(connect_timeout, PoolOptions::new().connect(conn_str)).await?;
             DbPool::MySQL(pool)
         }
         DatabaseType::SQLite => {
-            let pool = SqlitePool::connect(conn_str).await?;
+            // Missing files are created, after asking, before connecting.
+            let options = SqliteConnectOptions::from_str(conn_str)?;
+            let pool =
+                with_timeout(connect_timeout, PoolOptions::new().connect_with(options)).await?;
             DbPool::SQLite(pool)
         }
     };
 
     Ok(pool)
 }
+
+/// Bounds establishing the first connection by `connect_timeout`, like the
+/// libpq parameter of the same name. Waiting for a busy pool later on is
+/// not affected.
+async fn with_timeout<T>(
+    connect_timeout: Option<Duration>,
+    connecting: impl Future<Output = Result<T, sqlx::Error>>,
+) -> Result<T, sqlx::Error> {
+    let Some(timeout) = connect_timeout else {
+        return connecting.await;
+    };
+    tokio::time::timeout(timeout, connecting)
+        .await
+        .map_err(|_| {
+            sqlx::Error::Io(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("could not connect within {} s", timeout.as_secs()),
+            ))
+        })?
+}
+
+/// An in-memory SQLite database set up by the statements of `setup`, for
+/// tests. It has a single connection, since every in-memory connection is
+/// a database of its own.
+#[cfg(test)]
+pub async fn sqlite_test_pool(setup: &str) -> DbPool {
+    let pool = PoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::raw_sql(setup).execute(&pool).await.unwrap();
+    DbPool::SQLite(pool)
+}
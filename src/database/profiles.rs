@@ -0,0 +1,169 @@
+use super::connector::{ConnectionDetails, DatabaseType};
+use color_eyre::eyre::{Result, eyre};
+use inquire::{Confirm, Password, Text};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+const PROFILES_FILE: &str = "connections.json";
+
+/// A named set of connection details saved in the user's config directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionProfile {
+    pub name: String,
+    #[serde(flatten)]
+    pub details: ConnectionDetails,
+}
+
+impl std::fmt::Display for ConnectionProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name, self.details.db_type)
+    }
+}
+
+/// Returns `<config dir>/lazydata/connections.json`.
+pub fn profiles_path() -> Result<PathBuf> {
+    let dir = dirs::config_dir().ok_or_else(|| eyre!("Could not locate the config directory"))?;
+    Ok(dir.join("lazydata").join(PROFILES_FILE))
+}
+
+pub fn load_profiles() -> Result<Vec<ConnectionProfile>> {
+    let path = profiles_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&path)?;
+    let profiles = serde_json::from_str(&content)
+        .map_err(|e| eyre!("Could not parse {}: {}", path.display(), e))?;
+    Ok(profiles)
+}
+
+/// Adds a profile, replacing any existing profile with the same name.
+pub fn save_profile(profile: ConnectionProfile) -> Result<()> {
+    let mut profiles = load_profiles()?;
+    upsert_profile(&mut profiles, profile);
+    write_profiles(&profiles)
+}
+
+fn upsert_profile(profiles: &mut Vec<ConnectionProfile>, profile: ConnectionProfile) {
+    match profiles.iter_mut().find(|p| p.name == profile.name) {
+        Some(existing) => *existing = profile,
+        None => profiles.push(profile),
+    }
+}
+
+fn write_profiles(profiles: &[ConnectionProfile]) -> Result<()> {
+    let path = profiles_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    // The file may hold passwords, keep it private to the current user. The
+    // mode applies to a new file; an existing one is narrowed before writing.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(serde_json::to_string_pretty(profiles)?.as_bytes())?;
+
+    Ok(())
+}
+
+/// Asks for the password of a profile saved without one. Left empty, the
+/// usual fallbacks such as `.pgpass` or `.my.cnf` still apply.
+pub fn prompt_missing_password(profile: &mut ConnectionProfile) -> Result<()> {
+    if profile.details.db_type == DatabaseType::SQLite || profile.details.password.is_some() {
+        return Ok(());
+    }
+
+    let password = Password::new(&format!("Password for {}:", profile.name))
+        .without_confirmation()
+        .with_help_message("Leave empty to use the environment or password file")
+        .prompt()?;
+    profile.details.password = Some(password).filter(|p| !p.is_empty());
+    Ok(())
+}
+
+/// Asks whether the connection just made should be saved as a profile.
+pub fn prompt_save_profile(details: &ConnectionDetails) -> Result<()> {
+    let save = Confirm::new("💾 Save this connection as a profile?")
+        .with_default(false)
+        .prompt()?;
+    if !save {
+        return Ok(());
+    }
+
+    let default_name = details
+        .database
+        .clone()
+        .or_else(|| details.file_path.clone())
+        .unwrap_or_else(|| details.db_type.to_string());
+    let name = Text::new("Profile name:")
+        .with_default(&default_name)
+        .prompt()?;
+
+    let mut details = details.clone();
+    if details.password.is_some() {
+        let keep_password = Confirm::new("Store the password in the profile?")
+            .with_default(false)
+            .prompt()?;
+        if !keep_password {
+            details.password = None;
+        }
+    }
+
+    save_profile(ConnectionProfile { name, details })?;
+    println!("✅ Saved to {}", profiles_path()?.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, database: &str) -> ConnectionProfile {
+        ConnectionProfile {
+            name: name.to_string(),
+            details: ConnectionDetails {
+                db_type: DatabaseType::PostgreSQL,
+                host: Some("localhost".to_string()),
+                port: Some(5432),
+                username: Some("app".to_string()),
+                password: None,
+                database: Some(database.to_string()),
+                file_path: None,
+                params: vec![],
+            },
+        }
+    }
+
+    #[test]
+    fn test_upsert_replaces_by_name() {
+        let mut profiles = vec![profile("local", "app"), profile("staging", "app")];
+        upsert_profile(&mut profiles, profile("local", "app_dev"));
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].details.database.as_deref(), Some("app_dev"));
+
+        upsert_profile(&mut profiles, profile("prod", "app"));
+        assert_eq!(profiles.len(), 3);
+    }
+
+    #[test]
+    fn test_profile_json_round_trip() {
+        let profiles = vec![profile("local", "app")];
+        let json = serde_json::to_string(&profiles).unwrap();
+        let parsed: Vec<ConnectionProfile> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, profiles);
+    }
+}
@@ -0,0 +1,298 @@
+use super::fetch::{ObjectKind, SchemaObject, TableMetadata, human_size, mysql_text};
+use super::pool::DbPool;
+use crate::crud::browse::count_rows;
+use color_eyre::eyre::Result;
+use sqlx::{MySqlPool, PgPool, Row, SqlitePool};
+
+/// Label and value pairs describing a sidebar node, shown in the
+/// Properties tab of the data table.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Properties {
+    /// What the properties describe, e.g. `public.users`.
+    pub title: String,
+    pub entries: Vec<(String, String)>,
+}
+
+impl Properties {
+    fn new(title: String) -> Self {
+        Self {
+            title,
+            entries: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, label: &str, value: impl Into<String>) {
+        self.entries.push((label.to_string(), value.into()));
+    }
+}
+
+/// Shown for statistics an engine does not keep.
+const NOT_AVAILABLE: &str = "n/a";
+
+/// Collects the type, owner, sizes, row counts, maintenance times and comment
+/// of a table or view. The exact row count scans the whole table with a
+/// `COUNT(*)`, so it is only taken with `count_exactly`.
+pub async fn table_properties(
+    pool: &DbPool,
+    table: &TableMetadata,
+    count_exactly: bool,
+) -> Result<Properties> {
+    let mut properties = Properties::new(format!("{}.{}", table.schema, table.name));
+    properties.push("Type", table.table_type.clone());
+
+    let exact = match count_exactly {
+        true => Some(count_rows(pool, table).await?),
+        false => None,
+    };
+    match pool {
+        DbPool::Postgres(pg) => pg_table_properties(pg, table, exact, &mut properties).await?,
+        DbPool::MySQL(mysql) => {
+            mysql_table_properties(mysql, table, exact, &mut properties).await?
+        }
+        DbPool::SQLite(sqlite) => {
+            sqlite_table_properties(sqlite, table, exact, &mut properties).await?
+        }
+    }
+
+    Ok(properties)
+}
+
+/// Describes a function, procedure, sequence, type or extension from what the
+/// sidebar already knows about it.
+pub fn object_properties(object: &SchemaObject) -> Properties {
+    let mut properties = Properties::new(format!("{}.{}", object.schema, object.name));
+    let kind = match object.kind {
+        ObjectKind::Function => "function",
+        ObjectKind::Procedure => "procedure",
+        ObjectKind::Sequence => "sequence",
+        ObjectKind::EnumType => "enum type",
+        ObjectKind::CompositeType => "composite type",
+        ObjectKind::Extension => "extension",
+    };
+    properties.push("Type", kind);
+    properties.push("Schema", object.schema.clone());
+    properties.push("Name", object.name.clone());
+    properties.push("Identity", object.identity.clone());
+    if !object.detail.is_empty() {
+        properties.push("Detail", object.detail.clone());
+    }
+    properties
+}
+
+async fn pg_table_properties(
+    pool: &PgPool,
+    table: &TableMetadata,
+    exact: Option<usize>,
+    properties: &mut Properties,
+) -> Result<()> {
+    let row = sqlx::query(
+        r#"
+            SELECT
+                pg_get_userbyid(c.relowner)::TEXT AS owner,
+                pg_total_relation_size(c.oid) AS total_size,
+                pg_table_size(c.oid) - COALESCE(pg_total_relation_size(NULLIF(c.reltoastrelid, 0)), 0) AS table_size,
+                pg_indexes_size(c.oid) AS index_size,
+                COALESCE(pg_total_relation_size(NULLIF(c.reltoastrelid, 0)), 0) AS toast_size,
+                c.reltuples::BIGINT AS estimated_rows,
+                s.last_vacuum::TEXT AS last_vacuum,
+                s.last_autovacuum::TEXT AS last_autovacuum,
+                s.last_analyze::TEXT AS last_analyze,
+                s.last_autoanalyze::TEXT AS last_autoanalyze,
+                obj_description(c.oid, 'pg_class') AS comment
+            FROM pg_class c
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            LEFT JOIN pg_stat_all_tables s ON s.relid = c.oid
+            WHERE n.nspname = $1 AND c.relname = $2
+        "#,
+    )
+    .bind(&table.schema)
+    .bind(&table.name)
+    .fetch_one(pool)
+    .await?;
+
+    let never = |column: &str| -> String {
+        row.get::<Option<String>, _>(column)
+            .unwrap_or_else(|| "never".to_string())
+    };
+    let estimated: i64 = row.get("estimated_rows");
+
+    properties.push("Owner", row.get::<String, _>("owner"));
+    properties.push("Total size", human_size(row.get("total_size")));
+    properties.push("Table size", human_size(row.get("table_size")));
+    properties.push("Index size", human_size(row.get("index_size")));
+    properties.push("TOAST size", human_size(row.get("toast_size")));
+    // Postgres 14+ reports -1 for tables that were never vacuumed or analyzed.
+    properties.push(
+        "Estimated rows",
+        if estimated < 0 {
+            "unknown (never analyzed)".to_string()
+        } else {
+            estimated.to_string()
+        },
+    );
+    properties.push("Exact rows", exact_rows(exact));
+    properties.push("Last vacuum", never("last_vacuum"));
+    properties.push("Last autovacuum", never("last_autovacuum"));
+    properties.push("Last analyze", never("last_analyze"));
+    properties.push("Last autoanalyze", never("last_autoanalyze"));
+    properties.push(
+        "Comment",
+        row.get::<Option<String>, _>("comment").unwrap_or_default(),
+    );
+    Ok(())
+}
+
+async fn mysql_table_properties(
+    pool: &MySqlPool,
+    table: &TableMetadata,
+    exact: Option<usize>,
+    properties: &mut Properties,
+) -> Result<()> {
+    let row = sqlx::query(
+        r#"
+            SELECT
+                CAST(COALESCE(ENGINE, '') AS CHAR) AS engine,
+                CAST(COALESCE(DATA_LENGTH, 0) AS SIGNED) AS table_size,
+                CAST(COALESCE(INDEX_LENGTH, 0) AS SIGNED) AS index_size,
+                CAST(COALESCE(TABLE_ROWS, 0) AS SIGNED) AS estimated_rows,
+                CAST(COALESCE(UPDATE_TIME, 'never') AS CHAR) AS last_update,
+                CAST(COALESCE(CHECK_TIME, 'never') AS CHAR) AS last_check,
+                CAST(COALESCE(TABLE_COMMENT, '') AS CHAR) AS comment
+            FROM information_schema.TABLES
+            WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ?
+        "#,
+    )
+    .bind(&table.schema)
+    .bind(&table.name)
+    .fetch_one(pool)
+    .await?;
+
+    let table_size: i64 = row.get("table_size");
+    let index_size: i64 = row.get("index_size");
+
+    // MySQL tables have no owner, TOAST storage or vacuum.
+    properties.push("Engine", mysql_text(&row, "engine"));
+    properties.push("Owner", NOT_AVAILABLE);
+    properties.push("Total size", human_size(table_size + index_size));
+    properties.push("Table size", human_size(table_size));
+    properties.push("Index size", human_size(index_size));
+    properties.push("TOAST size", NOT_AVAILABLE);
+    properties.push(
+        "Estimated rows",
+        row.get::<i64, _>("estimated_rows").to_string(),
+    );
+    properties.push("Exact rows", exact_rows(exact));
+    properties.push("Last update", mysql_text(&row, "last_update"));
+    properties.push("Last check", mysql_text(&row, "last_check"));
+    properties.push("Comment", mysql_text(&row, "comment"));
+    Ok(())
+}
+
+async fn sqlite_table_properties(
+    pool: &SqlitePool,
+    table: &TableMetadata,
+    exact: Option<usize>,
+    properties: &mut Properties,
+) -> Result<()> {
+    // `dbstat` is missing unless SQLite was built with it.
+    let sizes = sqlx::query(
+        r#"
+            SELECT
+                COALESCE(SUM(CASE WHEN m.type = 'index' THEN 0 ELSE d.pgsize END), 0) AS table_size,
+                COALESCE(SUM(CASE WHEN m.type = 'index' THEN d.pgsize ELSE 0 END), 0) AS index_size
+            FROM dbstat d
+            JOIN sqlite_master m ON m.name = d.name
+            WHERE m.tbl_name = ?
+        "#,
+    )
+    .bind(&table.name)
+    .fetch_one(pool)
+    .await
+    .ok();
+    let size = |column: &str| -> String {
+        sizes
+            .as_ref()
+            .map(|row| human_size(row.get(column)))
+            .unwrap_or_else(|| NOT_AVAILABLE.to_string())
+    };
+    let total = sizes
+        .as_ref()
+        .map(|row| human_size(row.get::<i64, _>("table_size") + row.get::<i64, _>("index_size")))
+        .unwrap_or_else(|| NOT_AVAILABLE.to_string());
+
+    // `sqlite_stat1` only exists once ANALYZE has run. Like the sidebar, it
+    // prefers the whole-table row over those of indexes, which may be partial.
+    let estimate = sqlx::query_scalar::<_, String>(
+        "SELECT stat FROM sqlite_stat1 WHERE tbl = ? \
+         ORDER BY idx IS NOT NULL, CAST(stat AS INTEGER) DESC LIMIT 1",
+    )
+    .bind(&table.name)
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten()
+    .and_then(|stat| stat.split_whitespace().next().map(str::to_string))
+    .unwrap_or_else(|| "unknown (never analyzed)".to_string());
+
+    properties.push("Owner", NOT_AVAILABLE);
+    properties.push("Total size", total);
+    properties.push("Table size", size("table_size"));
+    properties.push("Index size", size("index_size"));
+    properties.push("TOAST size", NOT_AVAILABLE);
+    properties.push("Estimated rows", estimate);
+    properties.push("Exact rows", exact_rows(exact));
+    properties.push("Last vacuum", NOT_AVAILABLE);
+    properties.push("Last analyze", NOT_AVAILABLE);
+    properties.push("Comment", NOT_AVAILABLE);
+    Ok(())
+}
+
+fn exact_rows(exact: Option<usize>) -> String {
+    match exact {
+        Some(rows) => rows.to_string(),
+        None => "not counted (P in the sidebar counts them)".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::pool::sqlite_test_pool;
+
+    #[tokio::test]
+    async fn test_sqlite_table_properties() {
+        let pool = sqlite_test_pool(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);
+             CREATE INDEX users_a ON users (name) WHERE name = 'a';
+             INSERT INTO users (name) VALUES ('a'), ('b'), ('c');
+             ANALYZE;",
+        )
+        .await;
+
+        let table = TableMetadata {
+            schema: "main".to_string(),
+            name: "users".to_string(),
+            table_type: "table".to_string(),
+            ..Default::default()
+        };
+        let estimated = table_properties(&pool, &table, false).await.unwrap();
+        assert!(estimated.entries.contains(&(
+            "Exact rows".to_string(),
+            "not counted (P in the sidebar counts them)".to_string()
+        )));
+        let properties = table_properties(&pool, &table, true).await.unwrap();
+
+        let value = |label: &str| {
+            properties
+                .entries
+                .iter()
+                .find(|(l, _)| l == label)
+                .map(|(_, v)| v.as_str())
+        };
+        assert_eq!(properties.title, "main.users");
+        assert_eq!(value("Type"), Some("table"));
+        assert_eq!(value("Estimated rows"), Some("3"));
+        assert_eq!(value("Exact rows"), Some("3"));
+        assert_eq!(value("TOAST size"), Some(NOT_AVAILABLE));
+    }
+}
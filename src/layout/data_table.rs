@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use ratatui::layout::{Constraint, Direction, Layout, Margin, Rect};
 use ratatui::style::palette::tailwind;
@@ -14,8 +14,10 @@ use unicode_width::UnicodeWidthStr;
 
 use crate::app::Focus;
 use crate::components::tabs::StatefulTabs;
+use crate::database::fetch::TableMetadata;
+use crate::database::properties::Properties;
 use crate::style::theme::COLOR_BLOCK_BG;
-use crate::style::{DefaultStyle, StyleProvider};
+use crate::style::{DefaultStyle, SPINNER, StyleProvider};
 use arboard::Clipboard;
 use serde_json::Value;
 
@@ -62,6 +64,8 @@ pub struct DynamicData {
     pub rows: Vec<Vec<String>>,
     pub column_widths: Vec<u16>,
     pub min_column_widths: Vec<u16>,
+    /// Whether rows were left unfetched at the row cap.
+    pub truncated: bool,
 }
 
 impl DynamicData {
@@ -73,9 +77,31 @@ impl DynamicData {
             rows,
             column_widths,
             min_column_widths,
+            truncated: false,
         }
     }
 
+    /// Appends a batch streamed in for the same result set.
+    pub fn extend(&mut self, batch: DynamicData) {
+        if self.headers.is_empty() {
+            self.headers = batch.headers;
+            self.column_widths = batch.column_widths;
+            self.min_column_widths = batch.min_column_widths;
+        } else {
+            for (width, batch_width) in self.column_widths.iter_mut().zip(&batch.column_widths) {
+                *width = (*width).max(*batch_width);
+            }
+            for (width, batch_width) in self
+                .min_column_widths
+                .iter_mut()
+                .zip(&batch.min_column_widths)
+            {
+                *width = (*width).max(*batch_width);
+            }
+        }
+        self.rows.extend(batch.rows);
+    }
+
     pub fn from_query_results(headers: Vec<String>, rows: Vec<Vec<String>>) -> Self {
         Self::new(headers, rows)
     }
@@ -123,6 +149,15 @@ impl DynamicData {
     }
 }
 
+/// A table opened from the sidebar. Only the current page is held in
+/// `DataTable::data`; other pages are fetched from the server on demand.
+#[derive(Debug, Clone)]
+pub struct BrowsedTable {
+    pub connection: String,
+    pub table: TableMetadata,
+    pub total_rows: usize,
+}
+
 pub struct DataTable<'a> {
     state: TableState,
     pub data: DynamicData,
@@ -134,14 +169,26 @@ pub struct DataTable<'a> {
     pub tabs: StatefulTabs<'a>,
     pub status_message: Option<String>,
     pub elapsed: Duration,
+    /// When the query running in the background started, if one is.
+    pub running: Option<Instant>,
     page_size: usize,
     pub current_page: usize,
+    pub browsing: Option<BrowsedTable>,
+    /// Properties of the sidebar node last inspected, for the Properties tab.
+    pub properties: Option<Properties>,
+    /// Result sets of a script, one tab each. The slot of the result set on
+    /// screen is left empty while its rows live in `data`.
+    results: Vec<DynamicData>,
+    active_result: usize,
+    /// Result set cut short at the row cap whose remaining rows can still
+    /// be fetched.
+    pub more_rows: Option<usize>,
 }
 
 impl<'a> DataTable<'a> {
     pub fn new(headers: Vec<String>, rows: Vec<Vec<String>>) -> Self {
         let data = DynamicData::from_query_results(headers, rows);
-        let mut tabs = StatefulTabs::new(vec!["Data Output", "Messages", "Query History"]);
+        let mut tabs = StatefulTabs::new(Self::tab_titles(1));
         if data.is_empty() {
             tabs.set_index(1);
         }
@@ -164,8 +211,164 @@ impl<'a> DataTable<'a> {
             tabs,
             status_message: None,
             elapsed: Duration::ZERO,
+            running: None,
             page_size: 100,
             current_page: 0,
+            browsing: None,
+            properties: None,
+            results: vec![DynamicData::new(vec![], vec![])],
+            active_result: 0,
+            more_rows: None,
+        }
+    }
+
+    /// Shows the result sets of a script, each in its own tab.
+    pub fn from_results(results: Vec<DynamicData>) -> Self {
+        let mut results: Vec<DynamicData> = results
+            .into_iter()
+            .map(|data| DynamicData::from_query_results(data.headers, data.rows))
+            .collect();
+        if results.len() <= 1 {
+            let data = results
+                .pop()
+                .unwrap_or_else(|| DynamicData::new(vec![], vec![]));
+            return Self::new(data.headers, data.rows);
+        }
+
+        let first = std::mem::replace(&mut results[0], DynamicData::new(vec![], vec![]));
+        let mut table = Self::new(first.headers, first.rows);
+        table.tabs = StatefulTabs::new(Self::tab_titles(results.len()));
+        table.results = results;
+        table
+    }
+
+    /// Adds a result set streaming in after the ones already shown, giving
+    /// each its own tab.
+    pub fn add_result(&mut self, data: DynamicData) {
+        let index = self.tabs.index;
+        let shown = self.results.len();
+        self.results.push(data);
+        self.tabs = StatefulTabs::new(Self::tab_titles(self.results.len()));
+        // Stay on the tab that was selected, which moves unless it holds a result.
+        self.tabs
+            .set_index(if index < shown { index } else { index + 1 });
+    }
+
+    /// Appends a streamed batch of rows to result set `result`.
+    pub fn extend_result(&mut self, result: usize, batch: DynamicData) {
+        let Some(data) = self.result_mut(result) else {
+            return;
+        };
+        data.extend(batch);
+        if result == self.active_result {
+            if self.state.selected().is_none() && !self.data.is_empty() {
+                self.state.select(Some(0));
+            }
+            self.vertical_scroll_state = self.vertical_scroll_state.content_length(
+                (self.get_current_page_rows().len().saturating_sub(1)) * ITEM_HEIGHT,
+            );
+            self.horizontal_scroll_state = self.horizontal_scroll_state.content_length(
+                self.data
+                    .column_widths
+                    .iter()
+                    .sum::<u16>()
+                    .saturating_sub(1) as usize,
+            );
+        }
+    }
+
+    pub fn set_truncated(&mut self, result: usize, truncated: bool) {
+        if let Some(data) = self.result_mut(result) {
+            data.truncated = truncated;
+        }
+    }
+
+    fn result_mut(&mut self, result: usize) -> Option<&mut DynamicData> {
+        match result == self.active_result {
+            true => Some(&mut self.data),
+            false => self.results.get_mut(result),
+        }
+    }
+
+    fn tab_titles(result_count: usize) -> Vec<String> {
+        let results = match result_count {
+            0 | 1 => vec!["Data Output".to_string()],
+            n => (1..=n).map(|i| format!("Result {}", i)).collect(),
+        };
+        results
+            .into_iter()
+            .chain(["Messages", "Query History", "Properties"].map(String::from))
+            .collect()
+    }
+
+    pub fn messages_tab(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn show_messages(&mut self) {
+        self.tabs.set_index(self.messages_tab());
+    }
+
+    pub fn show_properties(&mut self) {
+        self.tabs.set_index(self.messages_tab() + 2);
+    }
+
+    /// Switches tabs, bringing a result set's rows on screen.
+    pub fn select_tab(&mut self, index: usize) {
+        if index >= self.tabs.titles.len() {
+            return;
+        }
+        if index < self.results.len() && index != self.active_result {
+            std::mem::swap(&mut self.data, &mut self.results[self.active_result]);
+            std::mem::swap(&mut self.data, &mut self.results[index]);
+            self.active_result = index;
+
+            self.state = TableState::default().with_selected((!self.data.is_empty()).then_some(0));
+            self.horizontal_scroll = 0;
+            self.current_page = 0;
+            self.vertical_scroll_state = ScrollbarState::new(
+                (self.data.rows.len().min(100).saturating_sub(1)) * ITEM_HEIGHT,
+            );
+            self.horizontal_scroll_state = ScrollbarState::new(
+                self.data
+                    .column_widths
+                    .iter()
+                    .sum::<u16>()
+                    .saturating_sub(1) as usize,
+            );
+        }
+        self.tabs.set_index(index);
+    }
+
+    pub fn next_tab(&mut self) {
+        self.tabs.next();
+        self.select_tab(self.tabs.index);
+    }
+
+    pub fn previous_tab(&mut self) {
+        self.tabs.previous();
+        self.select_tab(self.tabs.index);
+    }
+
+    /// Shows one server-side page of a browsed table.
+    pub fn browse(browsing: BrowsedTable, page: usize, data: DynamicData) -> Self {
+        let mut table = Self::new(data.headers, data.rows);
+        table.tabs.set_index(0);
+        table.current_page = page;
+        table.browsing = Some(browsing);
+        table
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    /// Index in `data` of the first row of the current page. Browsed tables
+    /// only hold the current page.
+    fn page_start(&self) -> usize {
+        match self.browsing {
+            Some(_) => 0,
+            None => self.current_page * self.page_size,
         }
     }
 
@@ -174,6 +377,9 @@ impl<'a> DataTable<'a> {
     }
 
     pub fn total_pages(&self) -> usize {
+        if let Some(browsing) = &self.browsing {
+            return browsing.total_rows.div_ceil(self.page_size).max(1);
+        }
         if self.data.is_empty() {
             return 1;
         }
@@ -181,7 +387,7 @@ impl<'a> DataTable<'a> {
     }
 
     fn get_current_page_rows(&self) -> &[Vec<String>] {
-        let start_index = self.current_page * self.page_size;
+        let start_index = self.page_start();
         let end_index = (start_index + self.page_size).min(self.data.len());
         &self.data.rows()[start_index..end_index]
     }
@@ -282,6 +488,15 @@ impl<'a> DataTable<'a> {
             return;
         }
 
+        if self.browsing.is_some() {
+            let row_on_page = absolute_row.min(self.data.len().saturating_sub(1));
+            self.state.select(Some(row_on_page));
+            self.vertical_scroll_state = self
+                .vertical_scroll_state
+                .position(row_on_page * ITEM_HEIGHT);
+            return;
+        }
+
         let total_rows = self.data.len();
         let target_absolute_row = absolute_row.min(total_rows.saturating_sub(1));
 
@@ -339,12 +554,12 @@ impl<'a> DataTable<'a> {
     pub fn copy_selected_cell(&self) -> Option<String> {
         let content = match (self.state.selected(), self.state.selected_column()) {
             (Some(row_idx_on_page), Some(col_idx)) => {
-                let absolute_row_idx = self.current_page * self.page_size + row_idx_on_page;
+                let absolute_row_idx = self.page_start() + row_idx_on_page;
                 let adjusted_col = col_idx.saturating_sub(1) + self.horizontal_scroll;
                 let row = self.data.rows().get(absolute_row_idx)?;
 
                 if col_idx == 0 {
-                    (absolute_row_idx + 1).to_string()
+                    (self.current_page * self.page_size + row_idx_on_page + 1).to_string()
                 } else if adjusted_col < row.len() {
                     row[adjusted_col].clone()
                 } else {
@@ -363,8 +578,7 @@ impl<'a> DataTable<'a> {
 
     pub fn copy_selected_row(&self) -> Option<String> {
         let selected_row_index_on_page = self.state.selected()?;
-        let absolute_selected_row_index =
-            self.current_page * self.page_size + selected_row_index_on_page;
+        let absolute_selected_row_index = self.page_start() + selected_row_index_on_page;
 
         let headers = self.data.headers();
         let row_data = self.data.rows().get(absolute_selected_row_index)?;
@@ -448,8 +662,33 @@ impl<'a> DataTable<'a> {
         let query_info_area = main_layout[2];
 
         let base_style = Style::default().bg(COLOR_BLOCK_BG);
-        let total_rows_str = format!("Total Rows: {}", self.data.len());
-        let query_done_str = format!("Query Complete: {} ms", self.elapsed.as_millis());
+        let total_rows_str = match &self.browsing {
+            Some(browsing) => format!(
+                "{}.{}: {} rows",
+                browsing.table.schema, browsing.table.name, browsing.total_rows
+            ),
+            None if self.data.truncated => format!(
+                "Total Rows: {}+ (truncated{})",
+                self.data.len(),
+                match self.more_rows == Some(self.active_result) {
+                    true => ", m runs it again for more",
+                    false => "",
+                }
+            ),
+            None => format!("Total Rows: {}", self.data.len()),
+        };
+        let query_done_str = match self.running {
+            Some(started) => {
+                let elapsed = started.elapsed();
+                let frame = SPINNER[(elapsed.as_millis() / 100) as usize % SPINNER.len()];
+                format!(
+                    "{} Running: {:.1} s (Esc to cancel)",
+                    frame,
+                    elapsed.as_secs_f64()
+                )
+            }
+            None => format!("Query Complete: {} ms", self.elapsed.as_millis()),
+        };
         let pagination_info_str = format!("Page: {}/{}", self.current_page + 1, self.total_pages());
 
         let tab_lines = [total_rows_str, query_done_str, pagination_info_str]
@@ -470,8 +709,9 @@ impl<'a> DataTable<'a> {
             .block(Block::default().border_style(app_style.border_style(Focus::Table)));
         frame.render_widget(tabs_widget, tab_area);
 
+        let results = self.results.len();
         match self.tabs.index {
-            0 => {
+            i if i < results => {
                 self.set_colors();
                 if self.is_empty() {
                     let message = "No data output. Execute a query to get output";
@@ -482,7 +722,7 @@ impl<'a> DataTable<'a> {
                     self.render_scrollbar(frame, content_area);
                 }
             }
-            1 => {
+            i if i == results => {
                 let messages_block = Block::default()
                     .borders(Borders::ALL)
                     .border_style(app_style.border_style(Focus::Table))
@@ -491,7 +731,7 @@ impl<'a> DataTable<'a> {
                 let messages_paragraph = Paragraph::new(message).block(messages_block);
                 frame.render_widget(messages_paragraph, content_area);
             }
-            2 => {
+            i if i == results + 1 => {
                 let history_block = Block::default()
                     .borders(Borders::ALL)
                     .border_style(app_style.border_style(Focus::Table))
@@ -500,6 +740,7 @@ impl<'a> DataTable<'a> {
                     .block(history_block);
                 frame.render_widget(history_paragraph, content_area);
             }
+            i if i == results + 2 => self.render_properties(frame, content_area, &app_style),
             _ => {}
         }
     }
@@ -614,6 +855,35 @@ impl<'a> DataTable<'a> {
         frame.render_stateful_widget(t, area, &mut self.state);
     }
 
+    fn render_properties(&self, frame: &mut Frame, area: Rect, style: &DefaultStyle) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(style.border_style(Focus::Table))
+            .style(style.block_style());
+        let Some(properties) = &self.properties else {
+            let hint = "Press p on a sidebar node to show its properties, P to count rows too.";
+            frame.render_widget(Paragraph::new(hint).block(block), area);
+            return;
+        };
+
+        let label_width = properties
+            .entries
+            .iter()
+            .map(|(label, _)| label.width() as u16)
+            .max()
+            .unwrap_or_default();
+        let rows = properties.entries.iter().map(|(label, value)| {
+            Row::new([
+                Cell::from(label.clone()).style(Style::default().add_modifier(Modifier::BOLD)),
+                Cell::from(value.clone()),
+            ])
+        });
+        let table = Table::new(rows, [Constraint::Length(label_width), Constraint::Min(0)])
+            .column_spacing(2)
+            .block(block.title(format!(" {} ", properties.title)));
+        frame.render_widget(table, area);
+    }
+
     fn render_scrollbar(&mut self, frame: &mut Frame, area: Rect) {
         if self.data.is_empty() {
             return;
@@ -2,16 +2,24 @@ use color_eyre::eyre::Result;
 use ratatui::Frame;
 use ratatui::layout::Rect;
 use ratatui::style::{Color, Modifier, Style};
-use ratatui::text::Text;
+use ratatui::text::{Line, Text};
 use ratatui::widgets::{Block, Borders, Paragraph};
 use std::fmt;
+use std::ops::Range;
+use std::time::{Duration, Instant};
 use syntect::highlighting::ThemeSet;
 use syntect::parsing::SyntaxSet;
 use tui_textarea::{CursorMove, Input, Key, Scrolling, TextArea};
 
 use crate::app::Focus;
+use crate::database::connector::DatabaseType;
+use crate::style::theme::{COLOR_HIGHLIGHT_BG, COLOR_HIGHLIGHT_FG};
 use crate::style::{DefaultStyle, StyleProvider};
-use crate::utils::highlighter::highlight_sql;
+use crate::utils::highlighter::{highlight_range, highlight_sql};
+use crate::utils::script::statement_at;
+
+/// How long the text sent to the server stays highlighted.
+const FLASH_DURATION: Duration = Duration::from_millis(400);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
@@ -27,9 +35,13 @@ impl Mode {
             focus: current_focus.clone(),
         };
         let help = match self {
-            Self::Normal => "type i to enter insert mode",
+            Self::Normal => {
+                "type i to enter insert mode, F5 to run all, F6 to run the statement under the cursor"
+            }
             Self::Insert => "type Esc to back to normal mode",
-            Self::Visual => "type y to yank, type d to delete, type Esc to back to normal mode",
+            Self::Visual => {
+                "type y to yank, type d to delete, F6 to run the selection, type Esc to back to normal mode"
+            }
             Self::Operator(_) => "move cursor to apply operator",
         };
         let title = format!("{} MODE ({})", self, help);
@@ -72,6 +84,11 @@ pub struct QueryEditor {
     pub mode: Mode,
     pub pending: Input,
     pub textarea: TextArea<'static>,
+    /// Name of the connection F5 runs against, shown in the editor border.
+    pub active_connection: Option<String>,
+    /// Byte range of the text last run on its own, highlighted until
+    /// `FLASH_DURATION` has passed.
+    flash: Option<(Range<usize>, Instant)>,
 }
 
 impl QueryEditor {
@@ -80,7 +97,56 @@ impl QueryEditor {
             mode,
             pending: Input::default(),
             textarea: TextArea::default(),
+            active_connection: None,
+            flash: None,
+        }
+    }
+
+    /// Adds `text`, e.g. generated DDL, as a paragraph of its own below the
+    /// cursor's line. What is already written stays, and `u` undoes it.
+    pub fn insert_paragraph(&mut self, text: &str) {
+        let lines = self.textarea.lines();
+        if lines.iter().all(|line| line.trim().is_empty()) {
+            self.textarea = TextArea::default();
+            self.textarea.insert_str(text);
+            return;
+        }
+
+        let below = self.textarea.cursor().0 + 1 < lines.len();
+        self.textarea.move_cursor(CursorMove::End);
+        self.textarea.insert_str(format!("\n\n{}", text));
+        if below {
+            self.textarea.insert_str("\n");
+        }
+    }
+
+    /// The text to run on its own: the selection in visual mode, otherwise
+    /// the statement under the cursor, read as `dialect`. Its range flashes
+    /// briefly.
+    pub fn statement_to_run(&mut self, dialect: DatabaseType) -> Option<String> {
+        let text = self.textarea.lines().join("\n");
+        let range = match self.textarea.selection_range() {
+            Some((start, (row, col))) if self.mode == Mode::Visual => {
+                // Vim's text selection is inclusive
+                let line_len = self.textarea.lines()[row].chars().count();
+                let start = byte_offset(self.textarea.lines(), start);
+                let end = byte_offset(self.textarea.lines(), (row, (col + 1).min(line_len)));
+                let selected = &text[start..end];
+                let leading = selected.len() - selected.trim_start().len();
+                start + leading..start + leading + selected.trim().len()
+            }
+            _ => {
+                let cursor = byte_offset(self.textarea.lines(), self.textarea.cursor());
+                statement_at(&text, cursor, dialect)?.range
+            }
+        };
+        if range.is_empty() {
+            return None;
         }
+
+        let sql = text[range.clone()].to_string();
+        self.flash = Some((range, Instant::now()));
+        Some(sql)
     }
 
     pub fn draw(&mut self, frame: &mut Frame, area: Rect, current_focus: Focus) {
@@ -91,7 +157,7 @@ impl QueryEditor {
         let text = self.textarea.lines().join("\n");
         let cursor = self.textarea.cursor();
         self.textarea.set_cursor_style(self.mode.cursor_style());
-        let highlighted_lines = highlight_sql(
+        let mut highlighted_lines = highlight_sql(
             &text,
             &ps,
             theme,
@@ -99,8 +165,27 @@ impl QueryEditor {
             cursor.1,
             self.mode.cursor_style(),
         );
+        if let Some((range, started)) = &self.flash {
+            if started.elapsed() < FLASH_DURATION {
+                highlight_range(
+                    &mut highlighted_lines,
+                    position(&text, range.start),
+                    position(&text, range.end),
+                    Style::default()
+                        .fg(COLOR_HIGHLIGHT_FG)
+                        .bg(COLOR_HIGHLIGHT_BG),
+                );
+            } else {
+                self.flash = None;
+            }
+        }
 
-        let block = self.mode.block(&current_focus);
+        let mut block = self.mode.block(&current_focus);
+        if let Some(connection) = &self.active_connection {
+            block = block.title_bottom(
+                Line::from(format!(" 🔌 {} (F3 switch, F2 new) ", connection)).right_aligned(),
+            );
+        }
 
         let paragraph = Paragraph::new(Text::from(highlighted_lines))
             .block(block)
@@ -425,3 +510,73 @@ impl QueryEditor {
         }
     }
 }
+
+/// Byte offset in the `\n`-joined `lines` of a (row, column) position,
+/// where the column counts characters.
+fn byte_offset(lines: &[String], (row, col): (usize, usize)) -> usize {
+    let before: usize = lines[..row].iter().map(|line| line.len() + 1).sum();
+    let line = &lines[row];
+    before
+        + line
+            .char_indices()
+            .nth(col)
+            .map_or(line.len(), |(at, _)| at)
+}
+
+/// The (row, column) position of byte `offset` in `text`, the inverse of
+/// `byte_offset`.
+fn position(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let row = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |at| at + 1);
+    (row, before[line_start..].chars().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statement_to_run() {
+        let mut editor = QueryEditor::new(Mode::Normal);
+        editor.insert_paragraph("SELECT 1;\n\nSELECT 'é', 2\nFROM t;");
+        editor.textarea.move_cursor(CursorMove::Jump(3, 2));
+        assert_eq!(
+            editor.statement_to_run(DatabaseType::PostgreSQL).as_deref(),
+            Some("SELECT 'é', 2\nFROM t")
+        );
+
+        // `v` on the 'é' then `e` to the end of the word selects `'é'`.
+        editor.textarea.move_cursor(CursorMove::Jump(2, 7));
+        editor.handle_keys(Input {
+            key: Key::Char('v'),
+            ..Default::default()
+        });
+        editor.mode = Mode::Visual;
+        editor.textarea.move_cursor(CursorMove::Jump(2, 9));
+        assert_eq!(
+            editor.statement_to_run(DatabaseType::PostgreSQL).as_deref(),
+            Some("'é'")
+        );
+    }
+
+    #[test]
+    fn test_insert_paragraph_keeps_the_text() {
+        let mut editor = QueryEditor::new(Mode::Normal);
+        editor.insert_paragraph("SELECT 1;\nSELECT 2;");
+        editor.textarea.move_cursor(CursorMove::Jump(0, 3));
+        editor.insert_paragraph("CREATE TABLE t (n INT);");
+        assert_eq!(
+            editor.textarea.lines(),
+            ["SELECT 1;", "", "CREATE TABLE t (n INT);", "", "SELECT 2;"]
+        );
+    }
+
+    #[test]
+    fn test_position_inverts_byte_offset() {
+        let lines = ["ab".to_string(), "éx".to_string()];
+        let text = lines.join("\n");
+        assert_eq!(byte_offset(&lines, (1, 1)), 5);
+        assert_eq!(position(&text, 5), (1, 1));
+    }
+}
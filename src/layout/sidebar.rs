@@ -5,12 +5,22 @@ use crate::{
 use ratatui::layout::Rect;
 use ratatui::widgets::{Block, Scrollbar, ScrollbarOrientation};
 use ratatui::{Frame, widgets::Borders};
+use std::collections::HashSet;
 use tui_tree_widget::{Tree, TreeItem, TreeState};
 #[must_use]
 pub struct SideBar {
     pub state: TreeState<String>,
     pub items: Vec<TreeItem<'static, String>>,
     pub focus: Focus,
+    /// Fuzzy pattern the tree is narrowed to. Empty when not filtering.
+    pub filter: String,
+    /// Whether keys are being typed into the filter prompt.
+    pub filtering: bool,
+    /// Paths of the nodes matching `filter`, in tree order.
+    pub hits: Vec<Vec<String>>,
+    /// Nodes that were expanded before filtering opened the parents of the
+    /// hits, expanded again once the filter is cleared.
+    opened_before_filter: Option<HashSet<Vec<String>>>,
 }
 
 impl SideBar {
@@ -19,6 +29,10 @@ impl SideBar {
             state: TreeState::default(),
             items,
             focus,
+            filter: String::new(),
+            filtering: false,
+            hits: Vec::new(),
+            opened_before_filter: None,
         }
     }
 
@@ -30,6 +44,80 @@ impl SideBar {
         self.items = new_items;
     }
 
+    /// Opens the filter prompt with an empty pattern.
+    pub fn start_filter(&mut self) {
+        self.filtering = true;
+        self.filter.clear();
+        if self.opened_before_filter.is_none() {
+            self.opened_before_filter = Some(self.state.opened().clone());
+        }
+    }
+
+    /// Drops the filter and puts back the nodes expanded before it.
+    pub fn clear_filter(&mut self) {
+        self.filtering = false;
+        self.filter.clear();
+        if let Some(opened) = self.opened_before_filter.take() {
+            self.state.close_all();
+            for path in opened {
+                self.state.open(path);
+            }
+        }
+    }
+
+    /// Stores the nodes matching the filter and expands their parents so
+    /// that every match is visible.
+    pub fn update_hits(&mut self, hits: Vec<Vec<String>>) {
+        for hit in &hits {
+            for depth in 1..hit.len() {
+                self.state.open(hit[..depth].to_vec());
+            }
+        }
+        self.hits = hits;
+    }
+
+    /// Selects the next match after the selected node, or the previous one
+    /// when `forward` is false, wrapping around at either end.
+    pub fn jump_to_hit(&mut self, forward: bool) -> bool {
+        if self.hits.is_empty() {
+            return false;
+        }
+        let count = self.hits.len();
+        let current = self
+            .hits
+            .iter()
+            .position(|hit| hit.as_slice() == self.state.selected());
+        let next = match (current, forward) {
+            (Some(i), true) => (i + 1) % count,
+            (Some(i), false) => (i + count - 1) % count,
+            (None, true) => 0,
+            (None, false) => count - 1,
+        };
+        self.state.select(self.hits[next].clone())
+    }
+
+    fn title(&self) -> String {
+        if self.filtering {
+            return format!("Connections  /{}▏", self.filter);
+        }
+        if self.filter.is_empty() {
+            return "Connections".to_string();
+        }
+
+        let current = self
+            .hits
+            .iter()
+            .position(|hit| hit.as_slice() == self.state.selected())
+            .map(|i| (i + 1).to_string())
+            .unwrap_or_else(|| "-".to_string());
+        format!(
+            "Connections  /{}  [{}/{}]",
+            self.filter,
+            current,
+            self.hits.len()
+        )
+    }
+
     pub fn render(&mut self, frame: &mut Frame, area: Rect) {
         let style = DefaultStyle {
             focus: self.focus.clone(),
@@ -38,7 +126,7 @@ impl SideBar {
             .expect("tree item IDs must be unique")
             .block(
                 Block::bordered()
-                    .title("Tables")
+                    .title(self.title())
                     .borders(Borders::ALL)
                     .border_style(style.border_style(Focus::Sidebar))
                     .style(style.block_style()),
@@ -54,3 +142,42 @@ impl SideBar {
         frame.render_stateful_widget(widget, area, &mut self.state);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_jump_to_hit_wraps_around() {
+        let leaf = |id: &str| TreeItem::new_leaf(id.to_string(), id.to_string());
+        let root = TreeItem::new("db".to_string(), "db", vec![leaf("a"), leaf("b")]).unwrap();
+        let mut sidebar = SideBar::new(vec![root], Focus::Sidebar);
+        sidebar.update_hits(vec![path(&["db", "a"]), path(&["db", "b"])]);
+        assert!(sidebar.state.opened().contains(&path(&["db"])));
+
+        sidebar.jump_to_hit(true);
+        assert_eq!(sidebar.state.selected(), path(&["db", "a"]));
+        sidebar.jump_to_hit(true);
+        assert_eq!(sidebar.state.selected(), path(&["db", "b"]));
+        sidebar.jump_to_hit(true);
+        assert_eq!(sidebar.state.selected(), path(&["db", "a"]));
+        sidebar.jump_to_hit(false);
+        assert_eq!(sidebar.state.selected(), path(&["db", "b"]));
+    }
+
+    #[test]
+    fn test_clearing_the_filter_restores_expanded_nodes() {
+        let mut sidebar = SideBar::new(vec![], Focus::Sidebar);
+        sidebar.state.open(path(&["db"]));
+        sidebar.start_filter();
+        sidebar.update_hits(vec![path(&["other", "schema", "table"])]);
+        assert_eq!(sidebar.state.opened().len(), 3);
+
+        sidebar.clear_filter();
+        assert_eq!(sidebar.state.opened(), &HashSet::from([path(&["db"])]));
+    }
+}
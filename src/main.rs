@@ -1,19 +1,26 @@
 mod app;
+mod cli;
 mod components;
 mod crud;
 mod database;
 mod layout;
-mod state;
 mod style;
 mod utils;
 
 use app::App;
+use clap::Parser;
+use cli::Cli;
 use color_eyre::eyre::Result;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
+    let cli = Cli::parse();
+    let details = cli.connection_details()?;
     let mut app = App::default();
-    app.init().await?;
+    app.exact_counts = cli.exact_counts;
+    app.continue_on_error = cli.continue_on_error;
+    app.max_rows = cli.max_rows.get();
+    app.init(details).await?;
     Ok(())
 }
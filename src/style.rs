@@ -11,8 +11,12 @@ pub mod theme {
     pub const COLOR_HIGHLIGHT_BG: Color = Color::Rgb(137, 220, 235);
     pub const COLOR_HIGHLIGHT_FG: Color = Color::Black;
     pub const COLOR_BLACK: Color = Color::Black;
+    pub const COLOR_MATCH: Color = Color::Rgb(249, 226, 175);
 }
 
+/// Frames of the spinner shown while something loads.
+pub const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
 pub trait StyleProvider {
     fn border_style(&self, current: Focus) -> Style;
     fn block_style(&self) -> Style;
@@ -0,0 +1,89 @@
+use crate::style::theme;
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+
+/// Matches `pattern` against `text` as a case-insensitive subsequence and
+/// returns the character positions of `text` that matched, earliest first.
+/// Whitespace in the pattern is ignored.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<Vec<usize>> {
+    let mut pattern = pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .peekable();
+    let mut positions = Vec::new();
+
+    for (i, c) in text.chars().enumerate() {
+        let Some(&wanted) = pattern.peek() else {
+            break;
+        };
+        if c.to_lowercase().eq(std::iter::once(wanted)) {
+            positions.push(i);
+            pattern.next();
+        }
+    }
+
+    pattern.peek().is_none().then_some(positions)
+}
+
+/// Renders `name` with the characters at `positions` highlighted, followed by
+/// an unstyled `suffix`.
+pub fn highlight_match(name: &str, positions: &[usize], suffix: &str) -> Line<'static> {
+    let matched = Style::default()
+        .fg(theme::COLOR_MATCH)
+        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (i, c) in name.chars().enumerate() {
+        let is_match = positions.contains(&i);
+        if is_match != run_matched && !run.is_empty() {
+            let text = std::mem::take(&mut run);
+            spans.push(if run_matched {
+                Span::styled(text, matched)
+            } else {
+                Span::raw(text)
+            });
+        }
+        run_matched = is_match;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        spans.push(if run_matched {
+            Span::styled(run, matched)
+        } else {
+            Span::raw(run)
+        });
+    }
+    if !suffix.is_empty() {
+        spans.push(Span::raw(suffix.to_string()));
+    }
+
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match_subsequence() {
+        assert_eq!(fuzzy_match("usr", "users"), Some(vec![0, 1, 3]));
+        assert_eq!(fuzzy_match("UID", "user_id"), Some(vec![0, 5, 6]));
+        assert_eq!(fuzzy_match("o id", "order_id"), Some(vec![0, 6, 7]));
+        assert_eq!(fuzzy_match("", "users"), Some(vec![]));
+        assert_eq!(fuzzy_match("sx", "users"), None);
+    }
+
+    #[test]
+    fn test_highlight_match_groups_runs() {
+        let line = highlight_match("users", &[0, 1, 3], " (3 rows)");
+        let texts: Vec<&str> = line.spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(texts, vec!["us", "e", "r", "s", " (3 rows)"]);
+        assert_eq!(line.spans[0].style.fg, Some(theme::COLOR_MATCH));
+        assert_eq!(line.spans[1].style, Style::default());
+    }
+}
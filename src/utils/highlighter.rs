@@ -63,3 +63,40 @@ pub fn highlight_sql(
         })
         .collect()
 }
+
+/// Patches `style` onto the characters of `lines` from `start` up to `end`,
+/// both given as (row, column) in characters.
+pub fn highlight_range(
+    lines: &mut [Line<'static>],
+    start: (usize, usize),
+    end: (usize, usize),
+    style: Style,
+) {
+    for (row, line) in lines.iter_mut().enumerate().take(end.0 + 1).skip(start.0) {
+        let from = if row == start.0 { start.1 } else { 0 };
+        let to = if row == end.0 { end.1 } else { usize::MAX };
+        let mut col = 0;
+        let mut spans = Vec::new();
+
+        for span in line.spans.drain(..) {
+            let chars: Vec<char> = span.content.chars().collect();
+            let a = from.clamp(col, col + chars.len()) - col;
+            let b = to.clamp(col, col + chars.len()) - col;
+            col += chars.len();
+            if a == b {
+                spans.push(span);
+                continue;
+            }
+            for (part, part_style) in [
+                (&chars[..a], span.style),
+                (&chars[a..b], span.style.patch(style)),
+                (&chars[b..], span.style),
+            ] {
+                if !part.is_empty() {
+                    spans.push(Span::styled(part.iter().collect::<String>(), part_style));
+                }
+            }
+        }
+        line.spans = spans;
+    }
+}
@@ -1,3 +1,5 @@
+pub mod fuzzy;
 pub mod highlighter;
 pub mod query_timer;
 pub mod query_type;
+pub mod script;
@@ -1,22 +1,341 @@
-#[allow(clippy::upper_case_acronyms)]
-#[derive(Debug)]
-pub enum Query {
-    SELECT,
-    INSERT,
-    UPDATE,
-    DELETE,
-    UNKNOWN,
+use crate::database::connector::DatabaseType;
+
+/// Broad category of a SQL statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryKind {
+    /// Reads data: SELECT, VALUES, TABLE, SHOW, EXPLAIN, DESCRIBE, PRAGMA.
+    Read,
+    /// Changes rows: INSERT, UPDATE, DELETE, MERGE, REPLACE, UPSERT.
+    Write,
+    /// Changes the schema, after which cached metadata goes stale.
+    Ddl,
+    /// BEGIN, COMMIT, ROLLBACK and friends.
+    Transaction,
+    /// Anything else the server understands: CALL, SET, GRANT, VACUUM, ...
+    Other,
+}
+
+/// A statement classified by its leading keywords, ignoring comments,
+/// parentheses and the common table expressions of a `WITH` clause.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    pub kind: QueryKind,
+    /// The keyword naming the statement, e.g. `SELECT` for `WITH ... SELECT`.
+    pub keyword: String,
+    /// Whether the statement is expected to produce a result set, such as a
+    /// query or an `INSERT ... RETURNING`.
+    pub returns_rows: bool,
+    /// Whether the statement only reads: a `SELECT`, `VALUES` or `TABLE`
+    /// with no data-modifying CTE, `INTO` or row lock anywhere in it, so
+    /// stopping it early or running it again changes nothing.
+    pub read_only: bool,
 }
 
 impl Query {
-    pub fn from_sql(sql: &str) -> Self {
-        let trimmed = sql.trim_start().to_uppercase();
-        match trimmed.split_whitespace().next() {
-            Some("SELECT") => Query::SELECT,
-            Some("INSERT") => Query::INSERT,
-            Some("UPDATE") => Query::UPDATE,
-            Some("DELETE") => Query::DELETE,
-            _ => Query::UNKNOWN,
+    pub fn from_sql(sql: &str, dialect: DatabaseType) -> Self {
+        let words = code_words(sql, dialect, true);
+        let mut keyword = words.first().cloned().unwrap_or_default();
+
+        // The statement after the CTEs decides what a WITH query does.
+        if keyword == "WITH"
+            && let Some(main) = words.iter().skip(1).find(|word| {
+                matches!(
+                    word.as_str(),
+                    "SELECT" | "INSERT" | "UPDATE" | "DELETE" | "MERGE" | "VALUES" | "TABLE"
+                )
+            })
+        {
+            keyword = main.clone();
+        }
+
+        let kind = match keyword.as_str() {
+            "SELECT" | "VALUES" | "TABLE" | "SHOW" | "EXPLAIN" | "DESCRIBE" | "DESC" | "PRAGMA" => {
+                QueryKind::Read
+            }
+            "INSERT" | "UPDATE" | "DELETE" | "MERGE" | "REPLACE" | "UPSERT" => QueryKind::Write,
+            "CREATE" | "ALTER" | "DROP" | "TRUNCATE" | "RENAME" | "COMMENT" => QueryKind::Ddl,
+            "BEGIN" | "START" | "COMMIT" | "END" | "ROLLBACK" | "SAVEPOINT" | "RELEASE" => {
+                QueryKind::Transaction
+            }
+            _ => QueryKind::Other,
+        };
+
+        let returns_rows = match kind {
+            // `PRAGMA name = value` sets rather than reads.
+            QueryKind::Read => keyword != "PRAGMA" || !has_top_level(sql, b'=', dialect),
+            QueryKind::Write => words.iter().any(|word| word == "RETURNING"),
+            _ => false,
+        };
+
+        let read_only = matches!(keyword.as_str(), "SELECT" | "VALUES" | "TABLE")
+            && !code_words(sql, dialect, false).iter().any(|word| {
+                matches!(
+                    word.as_str(),
+                    "INSERT" | "UPDATE" | "DELETE" | "MERGE" | "INTO" | "LOCK" | "SHARE"
+                )
+            });
+
+        Self {
+            kind,
+            keyword,
+            returns_rows,
+            read_only,
+        }
+    }
+}
+
+/// Returns the end of the comment, string, quoted identifier or
+/// dollar-quoted body starting at byte `i`, or `None` when none starts there.
+/// MySQL also has `#` comments and backslash escapes in strings, which
+/// Postgres only allows in `E'...'` strings.
+pub fn skip_literal(sql: &str, i: usize, dialect: DatabaseType) -> Option<usize> {
+    let bytes = sql.as_bytes();
+    let rest = &bytes[i..];
+    let find_from = |start: usize, needle: &[u8]| {
+        bytes[start..]
+            .windows(needle.len())
+            .position(|window| window == needle)
+            .map_or(bytes.len(), |at| start + at + needle.len())
+    };
+
+    let escape_string = i
+        .checked_sub(1)
+        .is_none_or(|before| !is_word_byte(bytes[before]));
+
+    match rest {
+        [b'-', b'-', ..] => Some(find_from(i, b"\n")),
+        [b'#', ..] if dialect == DatabaseType::MySQL => Some(find_from(i, b"\n")),
+        [b'E' | b'e', b'\'', ..] if dialect == DatabaseType::PostgreSQL && escape_string => {
+            Some(skip_quoted(bytes, i + 1, true))
+        }
+        [b'/', b'*', ..] => {
+            // Postgres lets block comments nest.
+            let mut depth = 0;
+            let mut j = i;
+            while j < bytes.len() {
+                match &bytes[j..] {
+                    [b'/', b'*', ..] => {
+                        depth += 1;
+                        j += 2;
+                    }
+                    [b'*', b'/', ..] => {
+                        depth -= 1;
+                        j += 2;
+                        if depth == 0 {
+                            return Some(j);
+                        }
+                    }
+                    _ => j += 1,
+                }
+            }
+            Some(bytes.len())
+        }
+        [quote @ (b'\'' | b'"' | b'`'), ..] => Some(skip_quoted(
+            bytes,
+            i,
+            dialect == DatabaseType::MySQL && *quote != b'`',
+        )),
+        [b'$', ..] if dialect == DatabaseType::PostgreSQL => {
+            let tag_len = rest[1..].iter().position(|b| !is_word_byte(*b))?;
+            let tag = &rest[..tag_len + 2];
+            // `$1` is a parameter, not a tag.
+            if tag[tag_len + 1] != b'$' || tag.get(1).is_some_and(u8::is_ascii_digit) {
+                return None;
+            }
+            Some(find_from(i + tag.len(), tag))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the end of the quoted text opening at byte `i`. A doubled quote
+/// stands for itself, as does any byte after a backslash with `escapes`.
+fn skip_quoted(bytes: &[u8], i: usize, escapes: bool) -> usize {
+    let quote = bytes[i];
+    let mut j = i + 1;
+    while j < bytes.len() {
+        if escapes && bytes[j] == b'\\' {
+            j += 2;
+            continue;
+        }
+        if bytes[j] == quote {
+            if bytes.get(j + 1) == Some(&quote) {
+                j += 2;
+                continue;
+            }
+            return j + 1;
+        }
+        j += 1;
+    }
+    bytes.len()
+}
+
+fn is_word_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+/// Visits the bytes of the first statement in `sql` that are code, along
+/// with the parenthesis depth they sit at.
+fn scan_code(sql: &str, dialect: DatabaseType, mut visit: impl FnMut(usize, usize)) {
+    let bytes = sql.as_bytes();
+    let mut depth: usize = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(end) = skip_literal(sql, i, dialect) {
+            i = end;
+            continue;
+        }
+        match bytes[i] {
+            b';' => return,
+            b'(' => depth += 1,
+            b')' => depth = depth.saturating_sub(1),
+            _ => visit(i, depth),
+        }
+        i += 1;
+    }
+}
+
+/// Upper-cased words outside literals and comments. With `top_level`, only
+/// those outside parentheses; leading parentheses, as in
+/// `(SELECT 1) UNION (SELECT 2)`, are looked through.
+fn code_words(sql: &str, dialect: DatabaseType, top_level: bool) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut base = None;
+    let mut last = None;
+
+    scan_code(sql, dialect, |i, depth| {
+        let byte = sql.as_bytes()[i];
+        if !is_word_byte(byte) {
+            return;
+        }
+        let base = *base.get_or_insert(depth);
+        if top_level && depth != base {
+            return;
+        }
+        if last == Some(i.wrapping_sub(1)) {
+            words
+                .last_mut()
+                .unwrap()
+                .push(byte.to_ascii_uppercase() as char);
+        } else {
+            words.push((byte.to_ascii_uppercase() as char).to_string());
+        }
+        last = Some(i);
+    });
+
+    words
+}
+
+/// Whether `needle` appears in the first statement outside literals.
+fn has_top_level(sql: &str, needle: u8, dialect: DatabaseType) -> bool {
+    let mut found = false;
+    scan_code(sql, dialect, |i, _| found |= sql.as_bytes()[i] == needle);
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(sql: &str) -> (QueryKind, String, bool) {
+        let query = Query::from_sql(sql, DatabaseType::PostgreSQL);
+        (query.kind, query.keyword, query.returns_rows)
+    }
+
+    #[test]
+    fn test_from_sql_classifies_statements() {
+        use QueryKind::*;
+        let cases = [
+            ("select 1", Read, "SELECT", true),
+            (
+                "  -- note\n/* a /* nested */ one */ SELECT 1",
+                Read,
+                "SELECT",
+                true,
+            ),
+            ("(SELECT 1) UNION (SELECT 2)", Read, "SELECT", true),
+            (
+                "WITH x AS (DELETE FROM t RETURNING *) SELECT * FROM x",
+                Read,
+                "SELECT",
+                true,
+            ),
+            (
+                "WITH RECURSIVE t(n) AS (SELECT 1) INSERT INTO u SELECT n FROM t",
+                Write,
+                "INSERT",
+                false,
+            ),
+            (
+                "INSERT INTO t VALUES (1) RETURNING id",
+                Write,
+                "INSERT",
+                true,
+            ),
+            (
+                "UPDATE t SET note = 'returning' WHERE id = 1",
+                Write,
+                "UPDATE",
+                false,
+            ),
+            ("create table t (id int)", Ddl, "CREATE", false),
+            ("EXPLAIN ANALYZE SELECT 1", Read, "EXPLAIN", true),
+            ("SHOW search_path", Read, "SHOW", true),
+            ("PRAGMA table_info(users)", Read, "PRAGMA", true),
+            ("PRAGMA foreign_keys = ON", Read, "PRAGMA", false),
+            ("VALUES (1), (2)", Read, "VALUES", true),
+            ("BEGIN", Transaction, "BEGIN", false),
+            ("CALL noop()", Other, "CALL", false),
+            ("", Other, "", false),
+        ];
+        for (sql, kind, keyword, returns_rows) in cases {
+            assert_eq!(
+                classify(sql),
+                (kind, keyword.to_string(), returns_rows),
+                "{sql}"
+            );
         }
     }
+
+    #[test]
+    fn test_read_only_excludes_statements_that_change_data() {
+        let read_only = |sql| Query::from_sql(sql, DatabaseType::PostgreSQL).read_only;
+        assert!(read_only("SELECT * FROM t ORDER BY id"));
+        assert!(read_only("WITH x AS (SELECT 1) SELECT * FROM x"));
+        assert!(read_only("SELECT 'delete' AS \"update\""));
+        assert!(!read_only(
+            "WITH x AS (DELETE FROM t RETURNING *) SELECT * FROM x"
+        ));
+        assert!(!read_only("SELECT * INTO copy FROM t"));
+        assert!(!read_only("SELECT * FROM t FOR UPDATE"));
+        assert!(!read_only("INSERT INTO t VALUES (1) RETURNING id"));
+        assert!(!read_only("EXPLAIN ANALYZE DELETE FROM t"));
+    }
+
+    #[test]
+    fn test_skip_literal() {
+        let sql = "'it''s' $fn$ select ';' $fn$ $1 \"a\"\"b\"";
+        let skip = |i| skip_literal(sql, i, DatabaseType::PostgreSQL);
+        assert_eq!(skip(0), Some(7));
+        assert_eq!(skip(8), Some(28));
+        assert_eq!(skip(29), None);
+        assert_eq!(skip(32), Some(sql.len()));
+    }
+
+    #[test]
+    fn test_skip_literal_follows_the_dialect() {
+        use DatabaseType::*;
+        let sql = r"'O\'Brien; x' # note";
+        assert_eq!(skip_literal(sql, 0, MySQL), Some(13));
+        assert_eq!(skip_literal(sql, 14, MySQL), Some(sql.len()));
+        assert_eq!(skip_literal(sql, 0, PostgreSQL), Some(4));
+        assert_eq!(skip_literal(sql, 14, PostgreSQL), None);
+
+        let sql = r"E'it\'s;' || e'\\' || 'a\'";
+        assert_eq!(skip_literal(sql, 0, PostgreSQL), Some(9));
+        assert_eq!(skip_literal(sql, 13, PostgreSQL), Some(18));
+        assert_eq!(skip_literal(sql, 22, PostgreSQL), Some(sql.len()));
+        assert_eq!(skip_literal(sql, 0, SQLite), None);
+        assert_eq!(skip_literal("$$ a $$", 0, MySQL), None);
+    }
 }
@@ -0,0 +1,281 @@
+use super::query_type::skip_literal;
+use crate::database::connector::DatabaseType;
+use std::ops::Range;
+
+/// One statement of a script, without its delimiter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement {
+    pub sql: String,
+    /// Byte range of `sql` within the script.
+    pub range: Range<usize>,
+}
+
+/// Splits a script into statements at `;` outside quotes, dollar-quoted
+/// bodies and comments, as `dialect` spells them. A `DELIMITER //` line, as
+/// understood by the MySQL client, changes the delimiter for the statements
+/// that follow it. Statements holding nothing but comments are dropped.
+pub fn split_statements(script: &str, dialect: DatabaseType) -> Vec<Statement> {
+    split(script, dialect, false)
+}
+
+/// Finds the statement around byte `offset` of `script`, for running it on
+/// its own. Blank lines outside literals and bodies end a statement just as
+/// `;` does. Between two statements, the one before the offset is picked.
+pub fn statement_at(script: &str, offset: usize, dialect: DatabaseType) -> Option<Statement> {
+    let statements = split(script, dialect, true);
+    statements
+        .iter()
+        .rev()
+        .find(|statement| statement.range.start <= offset)
+        .or(statements.first())
+        .cloned()
+}
+
+/// Splits `script` as `split_statements` does, and with `blank_lines` also
+/// at blank lines where a `;` would end a statement.
+fn split(script: &str, dialect: DatabaseType, blank_lines: bool) -> Vec<Statement> {
+    let bytes = script.as_bytes();
+    let mut statements = Vec::new();
+    let mut delimiter = ";".to_string();
+    let mut start = 0;
+    let mut has_code = false;
+    let mut first_word: Option<String> = None;
+    // Depth of BEGIN ... END and CASE ... END in a CREATE statement, so the
+    // body of a SQLite trigger or a `BEGIN ATOMIC` function stays whole.
+    let mut block_depth: usize = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let line_start = i == 0 || bytes[i - 1] == b'\n';
+        if line_start
+            && !has_code
+            && let Some((new_delimiter, line_end)) = delimiter_command(script, i)
+        {
+            delimiter = new_delimiter;
+            i = line_end;
+            start = i;
+            continue;
+        }
+
+        if line_start
+            && blank_lines
+            && block_depth == 0
+            && delimiter == ";"
+            && let Some(line_end) = blank_line(script, i)
+        {
+            if has_code {
+                statements.push(statement(script, start..i));
+            }
+            // Comments before a blank line belong to no statement.
+            i = line_end;
+            start = i;
+            has_code = false;
+            first_word = None;
+            continue;
+        }
+
+        if bytes[i..].starts_with(delimiter.as_bytes()) && block_depth == 0 {
+            if has_code {
+                statements.push(statement(script, start..i));
+            }
+            i += delimiter.len();
+            start = i;
+            has_code = false;
+            first_word = None;
+            continue;
+        }
+
+        if let Some(end) = skip_literal(script, i, dialect) {
+            // Comments are not code, but strings and quoted names are.
+            has_code |= !matches!(
+                &bytes[i..],
+                [b'-', b'-', ..] | [b'/', b'*', ..] | [b'#', ..]
+            );
+            i = end;
+            continue;
+        }
+
+        if bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' {
+            let end = bytes[i..]
+                .iter()
+                .position(|b| !(b.is_ascii_alphanumeric() || *b == b'_'))
+                .map_or(bytes.len(), |len| i + len);
+            let word = script[i..end].to_ascii_uppercase();
+            if first_word.as_deref() == Some("CREATE") && delimiter == ";" {
+                match word.as_str() {
+                    "BEGIN" | "CASE" => block_depth += 1,
+                    "END" => block_depth = block_depth.saturating_sub(1),
+                    _ => {}
+                }
+            }
+            first_word.get_or_insert(word);
+            has_code = true;
+            i = end;
+            continue;
+        }
+
+        has_code |= !bytes[i].is_ascii_whitespace();
+        i += 1;
+    }
+
+    if has_code {
+        statements.push(statement(script, start..bytes.len()));
+    }
+    statements
+}
+
+/// Condenses a statement to one short line for listing it in messages.
+pub fn statement_summary(sql: &str) -> String {
+    const MAX_CHARS: usize = 60;
+    let line = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+    match line.char_indices().nth(MAX_CHARS) {
+        Some((at, _)) => format!("{}…", &line[..at]),
+        None => line,
+    }
+}
+
+/// Trims the whitespace around `range` of `script`.
+fn statement(script: &str, range: Range<usize>) -> Statement {
+    let text = &script[range.clone()];
+    let leading = text.len() - text.trim_start().len();
+    let trimmed = text.trim();
+    let start = range.start + leading;
+    Statement {
+        sql: trimmed.to_string(),
+        range: start..start + trimmed.len(),
+    }
+}
+
+/// Returns where the next line starts when the line starting at byte `i`
+/// is blank.
+fn blank_line(script: &str, i: usize) -> Option<usize> {
+    let line_end = script[i..].find('\n').map(|at| i + at + 1)?;
+    script[i..line_end].trim().is_empty().then_some(line_end)
+}
+
+/// Parses a `DELIMITER <token>` line starting at byte `i`, returning the new
+/// delimiter and where the next line starts.
+fn delimiter_command(script: &str, i: usize) -> Option<(String, usize)> {
+    let line_end = script[i..].find('\n').map_or(script.len(), |at| i + at + 1);
+    let mut words = script[i..line_end].split_whitespace();
+    if !words.next()?.eq_ignore_ascii_case("DELIMITER") {
+        return None;
+    }
+    Some((words.next()?.to_string(), line_end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use DatabaseType::*;
+
+    fn sqls(script: &str, dialect: DatabaseType) -> Vec<String> {
+        split_statements(script, dialect)
+            .into_iter()
+            .map(|statement| statement.sql)
+            .collect()
+    }
+
+    #[test]
+    fn test_split_respects_quotes_and_comments() {
+        let script =
+            "SELECT ';' AS a; -- one; two\nSELECT \"x;y\" FROM t /* ; */;\n\n-- trailing comment";
+        assert_eq!(
+            sqls(script, PostgreSQL),
+            [
+                "SELECT ';' AS a",
+                "-- one; two\nSELECT \"x;y\" FROM t /* ; */"
+            ]
+        );
+
+        let statements = split_statements(script, PostgreSQL);
+        assert_eq!(&script[statements[0].range.clone()], "SELECT ';' AS a");
+    }
+
+    #[test]
+    fn test_split_keeps_bodies_whole() {
+        let script = "CREATE FUNCTION f() RETURNS int AS $$ SELECT 1; $$ LANGUAGE sql;\n\
+                      CREATE TRIGGER t AFTER INSERT ON a BEGIN\n  \
+                        UPDATE b SET n = CASE WHEN n > 0 THEN n ELSE 0 END;\n  \
+                        DELETE FROM c;\n\
+                      END;\n\
+                      SELECT 2";
+        let statements = sqls(script, PostgreSQL);
+        assert_eq!(statements.len(), 3);
+        assert!(statements[1].ends_with("DELETE FROM c;\nEND"));
+        assert_eq!(statements[2], "SELECT 2");
+    }
+
+    #[test]
+    fn test_split_follows_the_dialect_of_strings_and_comments() {
+        let script = "INSERT INTO t VALUES ('O\\'Brien; x'); # a; b\nSELECT 1";
+        assert_eq!(
+            sqls(script, MySQL),
+            [r"INSERT INTO t VALUES ('O\'Brien; x')", "# a; b\nSELECT 1"]
+        );
+        assert_eq!(
+            sqls(script, PostgreSQL)[0],
+            r"INSERT INTO t VALUES ('O\'Brien"
+        );
+
+        let script = r"SELECT E'it\'s; fine', 'C:\'; SELECT 2";
+        assert_eq!(
+            sqls(script, PostgreSQL),
+            [r"SELECT E'it\'s; fine', 'C:\'", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn test_split_follows_mysql_delimiter() {
+        let script = "DELIMITER //\n\
+                      CREATE PROCEDURE p() BEGIN SELECT 1; SELECT 2; END //\n\
+                      DELIMITER ;\n\
+                      CALL p();";
+        assert_eq!(
+            sqls(script, MySQL),
+            [
+                "CREATE PROCEDURE p() BEGIN SELECT 1; SELECT 2; END",
+                "CALL p()"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_statement_at_stops_at_delimiters_and_blank_lines() {
+        let script = "SELECT 1; SELECT 2;\n\nSELECT 3\nFROM t\n\n-- note\n\nSELECT '\n\n'";
+        let at = |needle: &str| {
+            let offset = script.find(needle).unwrap();
+            statement_at(script, offset, SQLite).map(|statement| statement.sql)
+        };
+        assert_eq!(at("1;").as_deref(), Some("SELECT 1"));
+        assert_eq!(at(" SELECT 2").as_deref(), Some("SELECT 1"));
+        assert_eq!(at("2;").as_deref(), Some("SELECT 2"));
+        assert_eq!(at("FROM").as_deref(), Some("SELECT 3\nFROM t"));
+        assert_eq!(at("-- note").as_deref(), Some("SELECT 3\nFROM t"));
+        assert_eq!(at("'\n").as_deref(), Some("SELECT '\n\n'"));
+        assert_eq!(statement_at("  \n", 0, SQLite), None);
+    }
+
+    #[test]
+    fn test_statement_at_keeps_bodies_with_blank_lines_whole() {
+        let trigger = "CREATE TRIGGER t AFTER INSERT ON a BEGIN\n\n  \
+                       DELETE FROM c;\n\n\
+                       END";
+        let script = format!("SELECT 1\n\n{trigger};\n\nSELECT 2");
+        let at = |needle: &str, dialect| {
+            let offset = script.find(needle).unwrap();
+            statement_at(&script, offset, dialect).map(|statement| statement.sql)
+        };
+        assert_eq!(at("DELETE", SQLite).as_deref(), Some(trigger));
+        assert_eq!(at("END", SQLite).as_deref(), Some(trigger));
+        assert_eq!(at("SELECT 2", SQLite).as_deref(), Some("SELECT 2"));
+
+        let procedure = "CREATE PROCEDURE p()\nBEGIN\n\n  SELECT 1;\n\nEND";
+        let script = format!("DELIMITER //\n{procedure} //\nDELIMITER ;\n\nCALL p()");
+        let offset = script.find("SELECT").unwrap();
+        assert_eq!(
+            statement_at(&script, offset, MySQL).map(|statement| statement.sql),
+            Some(procedure.to_string())
+        );
+    }
+}